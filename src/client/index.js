@@ -1,3 +1,14 @@
 // Nothing here yet!  It will eventually subscribe to the wasm memory
 // The entrypoint is marked #[wasm_bindgen(start)], so it's called automatically when instantiated in the import
 import * as wasm from "fivedice";
+
+// Solver tables are optional - built with `make strategy`, and the game plays fine without them
+fetch("strategy.bin")
+  .then(response => {
+    if (!response.ok) {
+      throw new Error(response.statusText);
+    }
+    return response.arrayBuffer();
+  })
+  .then(buffer => wasm.load_strategy(new Uint8Array(buffer)))
+  .catch(e => console.warn("No strategy tables loaded:", e));
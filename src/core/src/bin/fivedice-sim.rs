@@ -0,0 +1,254 @@
+// fivedice-sim plays a batch of games with one kind of computer player and reports how they scored
+
+use fivedice_core::{
+    controller::ControllerKind,
+    names::Named,
+    rules::Ruleset,
+    scoring::JokerRule,
+    sim::{simulate, Summary},
+    solver::Strategy,
+};
+use serde_json::json;
+use std::{env, fs, process, rc::Rc, str::FromStr};
+
+const USAGE: &str = "Usage: fivedice-sim [--games N] [--player Random|Greedy|Optimal] \
+                     [--rules FILE] [--rule Forced|FreeChoice|NoJoker] [--seed N] \
+                     [--tables FILE] [--bucket POINTS] [--csv FILE] [--json FILE]";
+
+// Percentiles printed and written out
+const PERCENTILES: [f64; 7] = [1.0, 5.0, 25.0, 50.0, 75.0, 95.0, 99.0];
+
+// Widest histogram bar, in characters
+const BAR_WIDTH: usize = 50;
+
+/// Everything the command line can ask for
+struct Options {
+    games: usize,
+    player: ControllerKind,
+    rules: Ruleset,
+    seed: u64,
+    tables: Option<String>,
+    bucket: u16,
+    csv: Option<String>,
+    json: Option<String>,
+}
+
+/// Print a complaint and the usage, then give up
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    eprintln!("{}", USAGE);
+    process::exit(1);
+}
+
+/// A flag's value, parsed
+fn value<T: FromStr>(flag: &str, arg: Option<String>) -> T {
+    arg.and_then(|a| a.parse().ok())
+        .unwrap_or_else(|| fail(&format!("{} needs a number", flag)))
+}
+
+/// Rules read from a file, or given up on
+fn load_rules(path: &str) -> Ruleset {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| fail(&format!("Couldn't read {}: {}", path, e)));
+    text.parse()
+        .unwrap_or_else(|e| fail(&format!("Couldn't load {}: {}", path, e)))
+}
+
+fn parse_args() -> Options {
+    // A joker rule given on its own applies to whichever rules are played
+    let mut rule = None;
+    let mut ret = Options {
+        games: 1000,
+        player: ControllerKind::Greedy,
+        rules: Ruleset::default(),
+        seed: 0,
+        tables: None,
+        bucket: 25,
+        csv: None,
+        json: None,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--games" => ret.games = value(&flag, args.next()),
+            "--seed" => ret.seed = value(&flag, args.next()),
+            "--bucket" => {
+                ret.bucket = Some(value(&flag, args.next()))
+                    .filter(|bucket| *bucket >= 1)
+                    .unwrap_or_else(|| fail("--bucket needs a number of at least 1"))
+            }
+            "--player" => {
+                ret.player = args
+                    .next()
+                    .and_then(|name| ControllerKind::from_name(&name))
+                    .filter(|kind| *kind != ControllerKind::Human)
+                    .unwrap_or_else(|| fail("--player needs Random, Greedy or Optimal"))
+            }
+            "--rules" => {
+                let path = args.next().unwrap_or_else(|| fail("--rules needs a file"));
+                ret.rules = load_rules(&path);
+            }
+            "--rule" => {
+                rule = Some(
+                    args.next()
+                        .and_then(|name| JokerRule::from_name(&name))
+                        .unwrap_or_else(|| fail("--rule needs Forced, FreeChoice or NoJoker")),
+                )
+            }
+            "--tables" => ret.tables = args.next(),
+            "--csv" => ret.csv = args.next(),
+            "--json" => ret.json = args.next(),
+            other => fail(&format!("Unknown option {}", other)),
+        }
+    }
+    if let Some(rule) = rule {
+        ret.rules = ret.rules.with_joker_rule(rule);
+    }
+    ret
+}
+
+/// Tables for optimal play - read from a file if given, otherwise solved on the spot
+fn load_strategy(opts: &Options) -> Strategy {
+    match &opts.tables {
+        Some(path) => {
+            let bytes =
+                fs::read(path).unwrap_or_else(|e| fail(&format!("Couldn't read {}: {}", path, e)));
+            let strategy = Strategy::from_bytes(&bytes)
+                .unwrap_or_else(|e| fail(&format!("Couldn't load {}: {}", path, e)));
+            if !strategy.fits(&opts.rules) {
+                fail(&format!(
+                    "{} was solved for the standard rules with {:?} jokers",
+                    path,
+                    strategy.joker_rule()
+                ));
+            }
+            strategy
+        }
+        None => {
+            let rule = opts.rules.joker_rule();
+            if opts.rules != Ruleset::default().with_joker_rule(rule) {
+                fail("Optimal play only knows the standard rules");
+            }
+            eprintln!("Solving for {:?} jokers - pass --tables to skip this", rule);
+            Strategy::solve(rule)
+        }
+    }
+}
+
+/// The summary as plain text, for the terminal
+fn print_summary(summary: &Summary, bucket: u16) {
+    println!("Games: {}", summary.games());
+    println!("Mean: {:.2}", summary.mean());
+    println!("Stddev: {:.2}", summary.stddev());
+    for pct in PERCENTILES.iter() {
+        println!("P{}: {}", pct, summary.percentile(*pct));
+    }
+    println!("Upper bonus: {:.1}%", summary.upper_bonus_rate() * 100.0);
+    println!("AllFive: {:.1}%", summary.all_five_rate() * 100.0);
+    println!(
+        "AllFive bonus: {:.1}%",
+        summary.all_five_bonus_rate() * 100.0
+    );
+    println!("Category averages:");
+    for (category, average) in summary.category_averages() {
+        println!("  {:?}: {:.2}", category, average);
+    }
+    println!("Histogram:");
+    let histogram = summary.histogram(bucket);
+    let most = histogram.iter().map(|(_, n)| *n).max().unwrap_or(0).max(1);
+    for (low, count) in histogram {
+        println!(
+            "  {:>4}-{:<4} {:>7} {}",
+            low,
+            // The top bucket can reach past what a u16 holds
+            u32::from(low) + u32::from(bucket) - 1,
+            count,
+            "#".repeat(count * BAR_WIDTH / most)
+        );
+    }
+}
+
+/// The summary as statistic,value rows
+fn to_csv(summary: &Summary, bucket: u16) -> String {
+    let mut rows = vec![
+        "statistic,value".to_string(),
+        format!("games,{}", summary.games()),
+        format!("mean,{}", summary.mean()),
+        format!("stddev,{}", summary.stddev()),
+    ];
+    for pct in PERCENTILES.iter() {
+        rows.push(format!("p{},{}", pct, summary.percentile(*pct)));
+    }
+    rows.push(format!("upper_bonus_rate,{}", summary.upper_bonus_rate()));
+    rows.push(format!("all_five_rate,{}", summary.all_five_rate()));
+    rows.push(format!(
+        "all_five_bonus_rate,{}",
+        summary.all_five_bonus_rate()
+    ));
+    for (category, average) in summary.category_averages() {
+        rows.push(format!("average_{:?},{}", category, average));
+    }
+    for (low, count) in summary.histogram(bucket) {
+        rows.push(format!("histogram_{},{}", low, count));
+    }
+    rows.join("\n") + "\n"
+}
+
+/// The summary as a JSON object
+fn to_json(summary: &Summary, opts: &Options) -> String {
+    let percentiles: serde_json::Map<String, serde_json::Value> = PERCENTILES
+        .iter()
+        .map(|pct| (format!("p{}", pct), json!(summary.percentile(*pct))))
+        .collect();
+    let averages: serde_json::Map<String, serde_json::Value> = summary
+        .category_averages()
+        .into_iter()
+        .map(|(category, average)| (format!("{:?}", category), json!(average)))
+        .collect();
+    let histogram: Vec<_> = summary
+        .histogram(opts.bucket)
+        .into_iter()
+        .map(|(low, count)| json!({ "low": low, "count": count }))
+        .collect();
+    let report = json!({
+        "player": format!("{:?}", opts.player),
+        "rules": opts.rules.name(),
+        "rule": format!("{:?}", opts.rules.joker_rule()),
+        "seed": opts.seed,
+        "games": summary.games(),
+        "mean": summary.mean(),
+        "stddev": summary.stddev(),
+        "percentiles": percentiles,
+        "upper_bonus_rate": summary.upper_bonus_rate(),
+        "all_five_rate": summary.all_five_rate(),
+        "all_five_bonus_rate": summary.all_five_bonus_rate(),
+        "category_averages": averages,
+        "histogram": histogram,
+    });
+    serde_json::to_string_pretty(&report).unwrap() + "\n"
+}
+
+/// Write a report out, or give up trying
+fn write(path: &str, contents: &str) {
+    if let Err(e) = fs::write(path, contents) {
+        eprintln!("Couldn't write {}: {}", path, e);
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let opts = parse_args();
+    let strategy = match opts.player {
+        ControllerKind::Optimal => Some(Rc::new(load_strategy(&opts))),
+        _ => None,
+    };
+    let mut controller = opts.player.build(opts.seed, strategy).unwrap();
+    let summary = simulate(opts.games, &opts.rules, opts.seed, controller.as_mut());
+    print_summary(&summary, opts.bucket);
+    if let Some(path) = &opts.csv {
+        write(path, &to_csv(&summary, opts.bucket));
+    }
+    if let Some(path) = &opts.json {
+        write(path, &to_json(&summary, &opts));
+    }
+}
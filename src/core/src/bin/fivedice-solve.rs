@@ -0,0 +1,32 @@
+// fivedice-solve precomputes the optimal solitaire strategy and writes its tables out
+
+use fivedice_core::{names::Named, score::Score, scoring::JokerRule, solver::Strategy};
+use std::{env, fs, process};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: fivedice-solve OUTPUT [Forced|FreeChoice|NoJoker]");
+            process::exit(1);
+        }
+    };
+    let rule = match args.next() {
+        Some(name) => JokerRule::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown joker rule {}", name);
+            process::exit(1);
+        }),
+        None => JokerRule::default(),
+    };
+    let strategy = Strategy::solve(rule);
+    println!(
+        "Expected score with {:?} jokers: {:.2}",
+        rule,
+        strategy.expected_total(&Score::new())
+    );
+    if let Err(e) = fs::write(&path, strategy.to_bytes()) {
+        eprintln!("Couldn't write {}: {}", path, e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,207 @@
+// controller.rs picks moves for computer players, sent through the reducer like any click
+
+use crate::{
+    dice::SeededDice,
+    game::{FiveDiceMessage, Game},
+    names::{Cycle, Named},
+    solver::Strategy,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, rc::Rc};
+
+/// Anything that can choose a player's moves
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     controller::ControllerKind,
+///     dice::SeededDice,
+///     game::{FiveDiceMessage, Game, GamePhase},
+///     scoring::JokerRule,
+/// };
+///
+/// let dice = Box::new(SeededDice::new(11));
+/// let mut game = Game::with_players(&["Ann", "Bo", "Cy"], JokerRule::Forced, dice);
+/// let kinds = [ControllerKind::Random, ControllerKind::Greedy, ControllerKind::Optimal];
+/// for (idx, kind) in kinds.iter().enumerate() {
+///     game.reducer(FiveDiceMessage::SetController(idx, *kind)).unwrap();
+/// }
+/// let mut controllers: Vec<_> = kinds.iter().map(|k| k.build(5, None).unwrap()).collect();
+/// while game.phase() == GamePhase::Playing {
+///     let msg = controllers[game.active()].next_move(&game);
+///     game.reducer(msg).unwrap();
+/// }
+/// assert_eq!(game.turn(), 39);
+/// ```
+pub trait Controller: fmt::Debug {
+    /// The next message to send for the active player
+    fn next_move(&mut self, game: &Game) -> FiveDiceMessage;
+}
+
+/// Who picks the moves for a player
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControllerKind {
+    /// Moves come from clicks
+    #[default]
+    Human,
+    /// Any legal move, picked at random
+    Random,
+    /// Chases the most common face, then takes the most points on offer
+    Greedy,
+    /// Follows the solver's strategy tables
+    Optimal,
+}
+
+impl Named for ControllerKind {}
+
+impl Cycle for ControllerKind {
+    const ORDER: &'static [Self] = &[
+        ControllerKind::Human,
+        ControllerKind::Random,
+        ControllerKind::Greedy,
+        ControllerKind::Optimal,
+    ];
+}
+
+impl ControllerKind {
+    /// A controller of this kind, or None for a human
+    /// Optimal play falls back to greedy until there are strategy tables to follow
+    pub fn build(self, seed: u64, strategy: Option<Rc<Strategy>>) -> Option<Box<dyn Controller>> {
+        use ControllerKind::*;
+        match (self, strategy) {
+            (Human, _) => None,
+            (Random, _) => Some(Box::new(RandomController::new(seed))),
+            (Greedy, _) | (Optimal, None) => Some(Box::new(GreedyController)),
+            (Optimal, Some(strategy)) => Some(Box::new(OptimalController::new(strategy))),
+        }
+    }
+}
+
+/// The open box worth the most for the active hand right now, column weight included,
+/// earliest on a tie
+fn best_immediate(game: &Game) -> FiveDiceMessage {
+    let player = game.active_player();
+    let (score, hand) = (player.score(), player.hand());
+    let mut best = None;
+    for column in 0..score.columns() {
+        for category in game.rules().categories() {
+            if !score.can_take(column, category, hand, game.rules()) {
+                continue;
+            }
+            let points = score.weight(column).saturating_mul(score.points_for(
+                column,
+                category,
+                hand,
+                game.rules(),
+            ));
+            if best.is_none_or(|(_, _, p)| points > p) {
+                best = Some((category, column, points));
+            }
+        }
+    }
+    match best {
+        Some((category, column, _)) => FiveDiceMessage::TakeScore(category, column),
+        // Nothing open means the turn can't be scored - rolling is all that's left
+        None => FiveDiceMessage::RollDice,
+    }
+}
+
+/// Toggle the first die whose hold doesn't match, if any
+fn fix_holds(game: &Game, wanted: &[bool]) -> Option<FiveDiceMessage> {
+    game.active_player()
+        .hand()
+        .dice
+        .iter()
+        .zip(wanted.iter())
+        .position(|(die, want)| die.is_held() != *want)
+        .map(FiveDiceMessage::HoldDie)
+}
+
+/// Picks uniformly from every legal move
+#[derive(Debug, Clone)]
+pub struct RandomController {
+    rng: SeededDice,
+}
+
+impl RandomController {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SeededDice::new(seed),
+        }
+    }
+}
+
+impl Controller for RandomController {
+    fn next_move(&mut self, game: &Game) -> FiveDiceMessage {
+        let moves = game.legal_moves();
+        if moves.is_empty() {
+            return FiveDiceMessage::RollDice;
+        }
+        moves[self.rng.below(moves.len())]
+    }
+}
+
+/// Holds the most common face while rolls remain, then scores where it earns most right now
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GreedyController;
+
+impl Controller for GreedyController {
+    fn next_move(&mut self, game: &Game) -> FiveDiceMessage {
+        let hand = game.active_player().hand();
+        if !hand.has_rolled() {
+            return FiveDiceMessage::RollDice;
+        }
+        if hand.remaining_rolls > 0 {
+            // Highest face on a tie, for the bigger upper score
+            let values = hand.values();
+            let target = (1..=6)
+                .max_by_key(|face| (values.iter().filter(|v| *v == face).count(), *face))
+                .unwrap();
+            let wanted: Vec<bool> = values.iter().map(|v| *v == target).collect();
+            if let Some(msg) = fix_holds(game, &wanted) {
+                return msg;
+            }
+            if !wanted.iter().all(|held| *held) {
+                return FiveDiceMessage::RollDice;
+            }
+        }
+        best_immediate(game)
+    }
+}
+
+/// Plays the solver's strategy, one hold at a time
+#[derive(Debug, Clone)]
+pub struct OptimalController {
+    strategy: Rc<Strategy>,
+}
+
+impl OptimalController {
+    pub fn new(strategy: Rc<Strategy>) -> Self {
+        Self { strategy }
+    }
+}
+
+impl Controller for OptimalController {
+    fn next_move(&mut self, game: &Game) -> FiveDiceMessage {
+        let player = game.active_player();
+        let (score, hand) = (player.score(), player.hand());
+        if !hand.has_rolled() {
+            return FiveDiceMessage::RollDice;
+        }
+        if let Some(advice) = self.strategy.advise_hold(score, hand) {
+            if let Some(msg) = fix_holds(game, &advice.held) {
+                return msg;
+            }
+            if !advice.held.iter().all(|held| *held) {
+                return FiveDiceMessage::RollDice;
+            }
+        }
+        // Tables solved under another joker rule might suggest a box this game won't allow
+        match self.strategy.advise_score(score, hand) {
+            Some(advice) if score.can_take(0, advice.category, hand, game.rules()) => {
+                FiveDiceMessage::TakeScore(advice.category, 0)
+            }
+            _ => best_immediate(game),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// dice.rs contains the dice and the sources of randomness for rolling them
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Each possible Die result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollResult {
+    One = 1,
+    Two = 2,
+    Three = 3,
+    Four = 4,
+    Five = 5,
+    Six = 6,
+}
+
+impl RollResult {
+    /// Get the result showing the given face, if it's on a six-sided die
+    pub fn from_face(face: u8) -> Option<Self> {
+        use RollResult::*;
+        match face {
+            1 => Some(One),
+            2 => Some(Two),
+            3 => Some(Three),
+            4 => Some(Four),
+            5 => Some(Five),
+            6 => Some(Six),
+            _ => None,
+        }
+    }
+}
+
+/// A single Die, can be held or not
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Die {
+    id: u8,
+    value: RollResult,
+    held: bool,
+}
+
+impl Die {
+    pub fn new(id: u8, value: RollResult) -> Self {
+        Self {
+            id,
+            value,
+            held: false,
+        }
+    }
+
+    /// Get a random die
+    pub fn get_random(id: u8, dice: &mut dyn DiceSource) -> Self {
+        Self::new(id, dice.roll())
+    }
+
+    /// Position of this die in its hand
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// The face currently showing
+    pub fn value(&self) -> RollResult {
+        self.value
+    }
+
+    /// Whether this die is kept out of the next roll
+    pub fn is_held(&self) -> bool {
+        self.held
+    }
+
+    /// Roll this die - no action if currently held
+    pub fn roll(&mut self, dice: &mut dyn DiceSource) {
+        if !self.held {
+            self.value = dice.roll();
+        }
+    }
+
+    /// Toggles whether this die is held
+    pub fn toggle_held(&mut self) {
+        self.held = !self.held;
+    }
+}
+
+/// Anything that can produce die rolls
+pub trait DiceSource: fmt::Debug {
+    /// Roll a single six-sided die
+    fn roll(&mut self) -> RollResult;
+
+    /// A copy of the generator to write into a save, if this source can be resumed
+    fn checkpoint(&self) -> Option<SeededDice> {
+        None
+    }
+}
+
+/// Deterministic dice from a 64-bit seed, using SplitMix64
+/// The same seed always produces the same sequence of rolls
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SeededDice {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededDice {
+    /// # Examples
+    /// ```
+    /// use fivedice_core::dice::{DiceSource, SeededDice};
+    ///
+    /// let mut first = SeededDice::new(42);
+    /// let mut second = SeededDice::new(42);
+    /// for _ in 0..100 {
+    ///     assert_eq!(first.roll(), second.roll());
+    /// }
+    /// assert_eq!(first.seed(), 42);
+    /// ```
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// The seed this source was started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A uniform pick from [0, n), for choosing between options rather than faces
+    pub fn below(&mut self, n: usize) -> usize {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as usize
+    }
+
+    /// Advance the generator
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl DiceSource for SeededDice {
+    fn roll(&mut self) -> RollResult {
+        // Scale the top 32 bits into [0, 6) to avoid modulo bias on the low bits
+        let face = ((self.next_u64() >> 32) * 6) >> 32;
+        RollResult::from_face(face as u8 + 1).unwrap()
+    }
+
+    fn checkpoint(&self) -> Option<SeededDice> {
+        Some(*self)
+    }
+}
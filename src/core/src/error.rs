@@ -0,0 +1,94 @@
+// error.rs contains the error type for rejected game actions
+use crate::scoring::ScoreType;
+use std::fmt;
+
+/// All the ways the rules can reject a message, or a stored file can fail to load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// Rules and players can only change before the first score
+    AlreadyStarted,
+    /// A game log line that doesn't parse or doesn't replay
+    BadLog(usize),
+    /// A ruleset line that doesn't parse or doesn't make sense
+    BadRules(usize),
+    /// A saved game that doesn't parse or doesn't hold a playable game
+    BadSave,
+    /// Strategy tables that weren't written by this version of the solver
+    BadTable,
+    /// Nothing but starting over once every card is full
+    GameOver,
+    /// Scoring a category the rules in play don't have
+    NotInRules(ScoreType),
+    /// Holding or scoring dice that haven't been rolled this turn
+    NotRolled,
+    /// Rolling or holding with every roll spent
+    NoRollsLeft,
+    /// Column index past the edge of the scorecard
+    NoSuchColumn(usize),
+    /// Die index past the end of the hand
+    NoSuchDie(usize),
+    /// Player index past the end of the table
+    NoSuchPlayer(usize),
+    /// No undone hold or score choice to put back
+    NothingToRedo,
+    /// No hold or score choice since the last roll to take back
+    NothingToUndo,
+    /// A player acting while it's someone else's turn
+    OutOfTurn { player: usize, active: usize },
+    /// Strategy tables solved under a different joker rule from the game's
+    RuleMismatch,
+    /// Storage wouldn't take the save
+    SaveFailed,
+    /// Scoring a category that's already filled
+    SlotTaken(ScoreType),
+    /// A forced joker must go somewhere else first
+    JokerRestricted(ScoreType),
+    /// Already at MAX_PLAYERS
+    TooManyPlayers,
+    /// Can't remove the last player
+    TooFewPlayers,
+    /// A save written by a version of the format this build doesn't read
+    UnsupportedSave(u32),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AlreadyStarted => write!(f, "The game has already started"),
+            Self::BadLog(line) => write!(f, "Game log is unreadable at line {}", line),
+            Self::BadRules(line) => write!(f, "Ruleset is unreadable at line {}", line),
+            Self::BadSave => write!(f, "Saved game is unreadable"),
+            Self::BadTable => write!(f, "Strategy table is unreadable"),
+            Self::GameOver => write!(f, "The game is over"),
+            Self::NotInRules(category) => write!(f, "{:?} isn't part of these rules", category),
+            Self::NotRolled => write!(f, "Roll the dice first"),
+            Self::NoRollsLeft => write!(f, "No rolls left this turn"),
+            Self::NoSuchColumn(idx) => write!(f, "There is no column {}", idx + 1),
+            Self::NoSuchDie(idx) => write!(f, "There is no die {}", idx),
+            Self::NoSuchPlayer(idx) => write!(f, "There is no player {}", idx + 1),
+            Self::NothingToRedo => write!(f, "Nothing to redo"),
+            Self::NothingToUndo => write!(f, "Nothing to undo since the last roll"),
+            Self::OutOfTurn { player, active } => write!(
+                f,
+                "Player {} can't act on player {}'s turn",
+                player + 1,
+                active + 1
+            ),
+            Self::RuleMismatch => write!(f, "Strategy table is for different rules"),
+            Self::SaveFailed => write!(f, "The game couldn't be saved"),
+            Self::SlotTaken(category) => write!(f, "{:?} is already taken", category),
+            Self::JokerRestricted(category) => {
+                write!(f, "This joker can't be scored in {:?} yet", category)
+            }
+            Self::TooManyPlayers => write!(f, "No room for another player"),
+            Self::TooFewPlayers => write!(f, "At least one player is needed"),
+            Self::UnsupportedSave(version) => {
+                write!(f, "Saved game version {} isn't supported", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+pub type Result<T> = std::result::Result<T, GameError>;
@@ -0,0 +1,804 @@
+// game.rs contains the game state and the reducer that drives it
+
+use crate::{
+    controller::ControllerKind,
+    dice::DiceSource,
+    error::{GameError, Result},
+    hand::Hand,
+    names::Named,
+    rules::{Ruleset, Variant},
+    score::Score,
+    scoring::{is_all_five, JokerRule, ScoreType},
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, fmt};
+
+// Most players that can share a hot-seat game
+pub const MAX_PLAYERS: usize = 8;
+
+/// The Player object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Player {
+    name: String,
+    score: Score,
+    current_hand: Hand,
+    // Who picks this player's moves
+    controller: ControllerKind,
+    // Hints asked for this game
+    hints: u32,
+}
+
+impl Player {
+    pub fn new(name: &str, rules: &Ruleset, dice: &mut dyn DiceSource) -> Self {
+        Self {
+            name: name.into(),
+            current_hand: Hand::with_size(rules.dice(), rules.rolls(), dice),
+            score: Score::for_rules(rules),
+            controller: ControllerKind::default(),
+            hints: 0,
+        }
+    }
+
+    /// This player's display name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This player's scorecard
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// The dice this player is currently rolling
+    pub fn hand(&self) -> &Hand {
+        &self.current_hand
+    }
+
+    /// Who picks this player's moves
+    pub fn controller(&self) -> ControllerKind {
+        self.controller
+    }
+
+    /// How many hints this player has asked for this game
+    pub fn hints(&self) -> u32 {
+        self.hints
+    }
+}
+
+// All the various ways the game can be interacted with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FiveDiceMessage {
+    AddPlayer,
+    Hint,
+    HoldDie(usize),
+    Redo,
+    RemovePlayer,
+    RollDice,
+    SetController(usize, ControllerKind),
+    SetJokerRule(JokerRule),
+    SetVariant(Variant),
+    StartOver,
+    /// Score the hand in a category, in a scorecard column counted from 0
+    TakeScore(ScoreType, usize),
+    Undo,
+}
+
+impl FiveDiceMessage {
+    /// Parse a message written out by its Display impl
+    pub fn from_line(line: &str) -> Option<Self> {
+        use FiveDiceMessage::*;
+        let mut words = line.split_whitespace();
+        let msg = match (words.next()?, words.next()) {
+            ("AddPlayer", None) => AddPlayer,
+            ("Hint", None) => Hint,
+            ("HoldDie", Some(idx)) => HoldDie(idx.parse().ok()?),
+            ("Redo", None) => Redo,
+            ("RemovePlayer", None) => RemovePlayer,
+            ("RollDice", None) => RollDice,
+            ("SetController", Some(idx)) => {
+                SetController(idx.parse().ok()?, ControllerKind::from_name(words.next()?)?)
+            }
+            ("SetJokerRule", Some(rule)) => SetJokerRule(JokerRule::from_name(rule)?),
+            ("SetVariant", Some(variant)) => SetVariant(Variant::from_name(variant)?),
+            ("StartOver", None) => StartOver,
+            // Logs from single-column games leave the column off
+            ("TakeScore", Some(category)) => TakeScore(
+                ScoreType::from_name(category)?,
+                match words.next() {
+                    Some(column) => column.parse().ok()?,
+                    None => 0,
+                },
+            ),
+            ("Undo", None) => Undo,
+            _ => return None,
+        };
+        // Nothing may trail the payload
+        match words.next() {
+            Some(_) => None,
+            None => Some(msg),
+        }
+    }
+}
+
+impl fmt::Display for FiveDiceMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use FiveDiceMessage::*;
+        match self {
+            HoldDie(idx) => write!(f, "HoldDie {}", idx),
+            SetController(idx, kind) => write!(f, "SetController {} {:?}", idx, kind),
+            SetJokerRule(rule) => write!(f, "SetJokerRule {:?}", rule),
+            SetVariant(variant) => write!(f, "SetVariant {:?}", variant),
+            TakeScore(category, 0) => write!(f, "TakeScore {:?}", category),
+            TakeScore(category, column) => write!(f, "TakeScore {:?} {}", category, column),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Where the game is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GamePhase {
+    Playing,
+    Finished,
+}
+
+// Only seeded dice can be written out, and they always come back seeded
+mod saved_dice {
+    use crate::dice::{DiceSource, SeededDice};
+    use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    // serde hands over a reference to the field itself
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(
+        dice: &Box<dyn DiceSource>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        dice.checkpoint()
+            .ok_or_else(|| S::Error::custom("these dice can't be saved"))?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<dyn DiceSource>, D::Error> {
+        Ok(Box::new(SeededDice::deserialize(deserializer)?))
+    }
+}
+
+/// Everything an undo needs to put back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    players: Vec<Player>,
+    active: usize,
+    phase: GamePhase,
+    round: usize,
+    turn: usize,
+    winner: Option<usize>,
+}
+
+/// The Game object
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     dice::SeededDice,
+///     error::GameError,
+///     game::{FiveDiceMessage, Game, GamePhase},
+///     scoring::{JokerRule, ScoreType},
+/// };
+///
+/// let dice = Box::new(SeededDice::new(7));
+/// let mut game = Game::with_players(&["Ann", "Bo"], JokerRule::FreeChoice, dice);
+/// for category in ScoreType::ALL.iter() {
+///     game.reducer(FiveDiceMessage::RollDice).unwrap();
+///     game.reducer(FiveDiceMessage::TakeScore(*category, 0)).unwrap();
+///     assert_eq!(game.active_player().name(), "Bo");
+///     assert_eq!(
+///         game.reducer(FiveDiceMessage::TakeScore(*category, 0)),
+///         Err(GameError::NotRolled)
+///     );
+///     game.reducer(FiveDiceMessage::RollDice).unwrap();
+///     game.reducer(FiveDiceMessage::TakeScore(*category, 0)).unwrap();
+/// }
+/// assert_eq!(game.phase(), GamePhase::Finished);
+/// assert_eq!(game.reducer(FiveDiceMessage::RollDice), Err(GameError::GameOver));
+/// // The last pick can still be taken back, but not the roll before it
+/// game.reducer(FiveDiceMessage::Undo).unwrap();
+/// assert_eq!(game.phase(), GamePhase::Playing);
+/// assert_eq!(game.reducer(FiveDiceMessage::Undo), Err(GameError::NothingToUndo));
+/// game.reducer(FiveDiceMessage::Redo).unwrap();
+/// assert_eq!(game.turn(), 26);
+/// assert_eq!(game.standings().len(), 2);
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Game {
+    // Hot-seat players, taking turns in order
+    players: Vec<Player>,
+    // Index into players of whoever is rolling
+    active: usize,
+    phase: GamePhase,
+    // 1-indexed, up to the rules' rounds
+    round: usize,
+    // Total turns completed so far, across all players
+    turn: usize,
+    // Whoever ended the game early by winning outright
+    winner: Option<usize>,
+    rules: Ruleset,
+    // Where every roll comes from
+    #[serde(with = "saved_dice")]
+    dice: Box<dyn DiceSource>,
+    // States to step back and forward through - emptied by every roll
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+}
+
+impl Game {
+    /// Start a solo game with the default rules
+    pub fn new(dice: Box<dyn DiceSource>) -> Self {
+        Self::with_players(&["Player 1"], JokerRule::default(), dice)
+    }
+
+    /// Start a standard game with the given player names, joker policy and dice
+    /// Names past MAX_PLAYERS are dropped
+    pub fn with_players(names: &[&str], joker_rule: JokerRule, dice: Box<dyn DiceSource>) -> Self {
+        Self::with_rules(names, Ruleset::default().with_joker_rule(joker_rule), dice)
+    }
+
+    /// Start a game with the given player names, rules and dice
+    /// Names past MAX_PLAYERS are dropped
+    pub fn with_rules(names: &[&str], rules: Ruleset, mut dice: Box<dyn DiceSource>) -> Self {
+        Self {
+            players: names
+                .iter()
+                .take(MAX_PLAYERS)
+                .map(|name| Player::new(name, &rules, dice.as_mut()))
+                .collect(),
+            active: 0,
+            phase: GamePhase::Playing,
+            round: 1,
+            turn: 0,
+            winner: None,
+            rules,
+            dice,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Every player, in turn order
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Index of the player whose turn it is
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Get the player whose turn it is
+    pub fn active_player(&self) -> &Player {
+        &self.players[self.active]
+    }
+
+    /// Get a mutable reference to the player whose turn it is
+    fn active_player_mut(&mut self) -> &mut Player {
+        &mut self.players[self.active]
+    }
+
+    /// Where the game is in its lifecycle
+    pub fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// The current round, starting from 1
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Total turns completed so far
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// The player who won outright before every card was full, if anyone did
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// The rules in effect
+    pub fn rules(&self) -> &Ruleset {
+        &self.rules
+    }
+
+    /// Rounds in a whole game - one per category in each column
+    pub fn rounds(&self) -> usize {
+        self.rules.rounds()
+    }
+
+    /// The joker policy in effect
+    pub fn joker_rule(&self) -> JokerRule {
+        self.rules.joker_rule()
+    }
+
+    // Toggle one die on the active player
+    fn hold_die(&mut self, die_idx: usize) -> Result<()> {
+        let hand = &mut self.active_player_mut().current_hand;
+        if die_idx >= hand.size() {
+            return Err(GameError::NoSuchDie(die_idx));
+        }
+        if !hand.has_rolled() {
+            return Err(GameError::NotRolled);
+        }
+        if hand.remaining_rolls == 0 {
+            return Err(GameError::NoRollsLeft);
+        }
+        hand.dice[die_idx].toggle_held();
+        Ok(())
+    }
+
+    /// Handle all incoming messages, rejecting any the rules don't allow
+    /// TODO send an outgoing result?  Maybe use the memory tape for streaming events back
+    pub fn reducer(&mut self, msg: FiveDiceMessage) -> Result<()> {
+        use FiveDiceMessage::*;
+        // Once finished, only a fresh game can be started or the last pick taken back
+        if self.phase == GamePhase::Finished {
+            match msg {
+                StartOver | Undo => {}
+                _ => return Err(GameError::GameOver),
+            }
+        }
+        // Holds and score choices can be taken back, so remember where they started
+        let before = match msg {
+            HoldDie(_) | TakeScore(..) => Some(self.snapshot()),
+            _ => None,
+        };
+        match msg {
+            AddPlayer => self.add_player(),
+            Hint => self.use_hint(),
+            HoldDie(idx) => self.hold_die(idx),
+            Redo => self.redo(),
+            RemovePlayer => self.remove_player(),
+            RollDice => self.roll_dice(),
+            SetController(idx, kind) => self.set_controller(idx, kind),
+            SetJokerRule(rule) => self.set_joker_rule(rule),
+            SetVariant(variant) => self.set_variant(variant),
+            StartOver => {
+                self.reset();
+                Ok(())
+            }
+            TakeScore(category, column) => self.take_score(column, category),
+            Undo => self.undo(),
+        }?;
+        match (msg, before) {
+            // Neither changes the board, so they leave the history alone
+            (Undo, _) | (Redo, _) | (Hint, _) => {}
+            (_, Some(snapshot)) => {
+                self.undo_stack.push(snapshot);
+                self.redo_stack.clear();
+            }
+            // Rolls are final - nothing from before one can be undone
+            (_, None) => {
+                self.undo_stack.clear();
+                self.redo_stack.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// Every turn move the active player could make right now
+    pub fn legal_moves(&self) -> Vec<FiveDiceMessage> {
+        use FiveDiceMessage::*;
+        if self.phase == GamePhase::Finished {
+            return Vec::new();
+        }
+        let player = self.active_player();
+        let hand = player.hand();
+        let mut ret = Vec::new();
+        if hand.remaining_rolls > 0 {
+            ret.push(RollDice);
+        }
+        if hand.has_rolled() {
+            if hand.remaining_rolls > 0 {
+                ret.extend((0..hand.size()).map(HoldDie));
+            }
+            for column in 0..self.rules.columns().len() {
+                ret.extend(
+                    self.rules
+                        .categories()
+                        .filter(|c| player.score().can_take(column, *c, hand, &self.rules))
+                        .map(|c| TakeScore(c, column)),
+                );
+            }
+        }
+        ret
+    }
+
+    /// Whether there's a hold or score choice to take back
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Whether there's an undone hold or score choice to put back
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Capture the current state for the undo history
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            players: self.players.clone(),
+            active: self.active,
+            phase: self.phase,
+            round: self.round,
+            turn: self.turn,
+            winner: self.winner,
+        }
+    }
+
+    /// Put back a captured state
+    /// Hints used stay counted, whatever else is taken back
+    fn restore(&mut self, mut snapshot: Snapshot) {
+        for (restored, current) in snapshot.players.iter_mut().zip(self.players.iter()) {
+            restored.hints = current.hints;
+        }
+        self.players = snapshot.players;
+        self.active = snapshot.active;
+        self.phase = snapshot.phase;
+        self.round = snapshot.round;
+        self.turn = snapshot.turn;
+        self.winner = snapshot.winner;
+    }
+
+    /// Step back before the last hold or score choice
+    fn undo(&mut self) -> Result<()> {
+        let snapshot = self.undo_stack.pop().ok_or(GameError::NothingToUndo)?;
+        self.redo_stack.push(self.snapshot());
+        self.restore(snapshot);
+        Ok(())
+    }
+
+    /// Step forward to the last undone hold or score choice
+    fn redo(&mut self) -> Result<()> {
+        let snapshot = self.redo_stack.pop().ok_or(GameError::NothingToRedo)?;
+        self.undo_stack.push(self.snapshot());
+        self.restore(snapshot);
+        Ok(())
+    }
+
+    /// Handle a message sent on behalf of the given player
+    /// Dice and scoring messages are rejected unless it's that player's turn
+    pub fn reduce_as(&mut self, player: usize, msg: FiveDiceMessage) -> Result<()> {
+        use FiveDiceMessage::*;
+        match msg {
+            HoldDie(_) | RollDice | TakeScore(..) if player != self.active => {
+                Err(GameError::OutOfTurn {
+                    player,
+                    active: self.active,
+                })
+            }
+            _ => self.reducer(msg),
+        }
+    }
+
+    /// Start a fresh new game with the same players and rules
+    fn reset(&mut self) {
+        for player in self.players.iter_mut() {
+            *player = Player {
+                controller: player.controller,
+                ..Player::new(&player.name, &self.rules, self.dice.as_mut())
+            };
+        }
+        self.active = 0;
+        self.phase = GamePhase::Playing;
+        self.round = 1;
+        self.turn = 0;
+        self.winner = None;
+    }
+
+    /// Add another player - only allowed before the first score is taken
+    fn add_player(&mut self) -> Result<()> {
+        if self.turn > 0 {
+            return Err(GameError::AlreadyStarted);
+        }
+        if self.players.len() >= MAX_PLAYERS {
+            return Err(GameError::TooManyPlayers);
+        }
+        let name = format!("Player {}", self.players.len() + 1);
+        self.players
+            .push(Player::new(&name, &self.rules, self.dice.as_mut()));
+        Ok(())
+    }
+
+    /// Drop the last player - only allowed before the first score is taken
+    fn remove_player(&mut self) -> Result<()> {
+        if self.turn > 0 {
+            return Err(GameError::AlreadyStarted);
+        }
+        if self.players.len() <= 1 {
+            return Err(GameError::TooFewPlayers);
+        }
+        self.players.pop();
+        self.active = 0;
+        Ok(())
+    }
+
+    /// Hand a player over to a computer or back - only allowed before the first score is taken
+    fn set_controller(&mut self, idx: usize, kind: ControllerKind) -> Result<()> {
+        if self.turn > 0 {
+            return Err(GameError::AlreadyStarted);
+        }
+        let player = self
+            .players
+            .get_mut(idx)
+            .ok_or(GameError::NoSuchPlayer(idx))?;
+        player.controller = kind;
+        Ok(())
+    }
+
+    /// Count a hint for the active player - only once there's a roll to advise on
+    fn use_hint(&mut self) -> Result<()> {
+        let player = self.active_player_mut();
+        if !player.current_hand.has_rolled() {
+            return Err(GameError::NotRolled);
+        }
+        player.hints += 1;
+        Ok(())
+    }
+
+    /// Change the joker policy - only allowed before the first score is taken
+    fn set_joker_rule(&mut self, rule: JokerRule) -> Result<()> {
+        if self.turn > 0 {
+            return Err(GameError::AlreadyStarted);
+        }
+        self.rules.set_joker_rule(rule);
+        Ok(())
+    }
+
+    /// Switch to a built-in ruleset - only allowed before the first score is taken
+    /// Every card and hand is dealt again for the new rules
+    fn set_variant(&mut self, variant: Variant) -> Result<()> {
+        if self.turn > 0 {
+            return Err(GameError::AlreadyStarted);
+        }
+        self.rules = variant.rules();
+        self.reset();
+        Ok(())
+    }
+
+    /// Roll all unheld dice
+    fn roll_dice(&mut self) -> Result<()> {
+        let hand = &mut self.players[self.active].current_hand;
+        if hand.remaining_rolls == 0 {
+            return Err(GameError::NoRollsLeft);
+        }
+        hand.roll(self.dice.as_mut());
+        Ok(())
+    }
+
+    /// Score the current hand in the given column's category and pass the dice along
+    fn take_score(&mut self, column: usize, category: ScoreType) -> Result<()> {
+        let rules = &self.rules;
+        let player = &mut self.players[self.active];
+        let hand = player.current_hand.clone();
+        if !hand.has_rolled() {
+            return Err(GameError::NotRolled);
+        }
+        if rules.rule(category).is_none() {
+            return Err(GameError::NotInRules(category));
+        }
+        if column >= rules.columns().len() {
+            return Err(GameError::NoSuchColumn(column));
+        }
+        if !player.score.is_open_in(column, category) {
+            return Err(GameError::SlotTaken(category));
+        }
+        if !player.score.can_take(column, category, &hand, rules) {
+            return Err(GameError::JokerRestricted(category));
+        }
+        let points = player.score.points_for(column, category, &hand, rules);
+        player.score.award_all_five_bonus(column, &hand, rules);
+        player.score.take_in(column, category, points);
+        // A five of a kind served on the first roll ends the game there, where the rules say so
+        let outright = rules.served_all_five_wins() && hand.is_served() && is_all_five(&hand);
+        // Rolls left unused are saved for the player's next turn, where the rules allow
+        let saved = if rules.carries_rolls() {
+            hand.remaining_rolls
+        } else {
+            0
+        };
+        let rolls = rules.rolls().saturating_add(saved);
+        player.current_hand = Hand::with_size(rules.dice(), rolls, self.dice.as_mut());
+        if outright {
+            self.turn += 1;
+            self.winner = Some(self.active);
+            self.phase = GamePhase::Finished;
+        } else {
+            self.end_turn();
+        }
+        Ok(())
+    }
+
+    /// Rotate to the next player, finishing the game once every card is full
+    fn end_turn(&mut self) {
+        self.turn += 1;
+        if self.players.iter().all(|p| p.score.is_complete()) {
+            self.phase = GamePhase::Finished;
+            return;
+        }
+        self.active = (self.active + 1) % self.players.len();
+        if self.active == 0 {
+            self.round += 1;
+        }
+    }
+
+    /// Rank every player by total, breaking ties by upper section total
+    /// An outright winner comes first whatever the totals, and players still tied share a rank
+    pub fn standings(&self) -> Vec<(usize, &Player)> {
+        let key = |seat: usize| {
+            let p = &self.players[seat];
+            (
+                self.winner == Some(seat),
+                p.score.total(),
+                p.score.upper_subtotal(),
+            )
+        };
+        let mut sorted: Vec<usize> = (0..self.players.len()).collect();
+        sorted.sort_by_key(|seat| Reverse(key(*seat)));
+        let mut ret: Vec<(usize, &Player)> = Vec::with_capacity(sorted.len());
+        let mut prev: Option<usize> = None;
+        for (idx, seat) in sorted.into_iter().enumerate() {
+            let rank = match (prev, ret.last()) {
+                (Some(prev), Some((prev_rank, _))) if key(prev) == key(seat) => *prev_rank,
+                _ => idx + 1,
+            };
+            ret.push((rank, &self.players[seat]));
+            prev = Some(seat);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FiveDiceMessage, Game, GamePhase};
+    use crate::{
+        dice::SeededDice,
+        error::GameError,
+        rules::{Ruleset, Variant},
+        scoring::ScoreType,
+    };
+
+    /// A solo game switched to a built-in variant
+    fn game_of(variant: Variant, seed: u64) -> Game {
+        let mut game = Game::new(Box::new(SeededDice::new(seed)));
+        game.reducer(FiveDiceMessage::SetVariant(variant)).unwrap();
+        game
+    }
+
+    #[test]
+    fn dice_moves_need_a_roll_in_hand() {
+        let mut game = game_of(Variant::FiveDice, 5);
+        assert_eq!(
+            game.reducer(FiveDiceMessage::HoldDie(0)),
+            Err(GameError::NotRolled)
+        );
+        for _ in 0..3 {
+            game.reducer(FiveDiceMessage::RollDice).unwrap();
+        }
+        assert_eq!(
+            game.reducer(FiveDiceMessage::RollDice),
+            Err(GameError::NoRollsLeft)
+        );
+        assert_eq!(
+            game.reducer(FiveDiceMessage::HoldDie(0)),
+            Err(GameError::NoRollsLeft)
+        );
+        assert_eq!(
+            game.reducer(FiveDiceMessage::HoldDie(5)),
+            Err(GameError::NoSuchDie(5))
+        );
+        // Nothing turned down counts as a move
+        assert!(game.undo_stack.is_empty());
+    }
+
+    #[test]
+    fn only_the_active_player_moves_the_dice() {
+        let mut game = game_of(Variant::FiveDice, 5);
+        game.reducer(FiveDiceMessage::AddPlayer).unwrap();
+        let out_of_turn = Err(GameError::OutOfTurn {
+            player: 1,
+            active: 0,
+        });
+        assert_eq!(game.reduce_as(1, FiveDiceMessage::RollDice), out_of_turn);
+        game.reduce_as(0, FiveDiceMessage::RollDice).unwrap();
+        assert_eq!(game.reduce_as(1, FiveDiceMessage::HoldDie(0)), out_of_turn);
+        let take = FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 0);
+        assert_eq!(game.reduce_as(1, take), out_of_turn);
+        game.reduce_as(0, take).unwrap();
+        assert_eq!(game.active(), 1);
+        // Moves for the whole table don't belong to anyone's turn
+        game.reduce_as(0, FiveDiceMessage::Undo).unwrap();
+    }
+
+    #[test]
+    fn maxi_yatzy_saves_unused_rolls() {
+        let mut game = game_of(Variant::MaxiYatzy, 5);
+        game.reducer(FiveDiceMessage::RollDice).unwrap();
+        game.reducer(FiveDiceMessage::HoldDie(5)).unwrap();
+        assert_eq!(
+            game.reducer(FiveDiceMessage::HoldDie(6)),
+            Err(GameError::NoSuchDie(6))
+        );
+        game.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 0))
+            .unwrap();
+        let hand = game.active_player().hand();
+        assert_eq!((hand.size(), hand.remaining_rolls), (6, 5));
+    }
+
+    #[test]
+    fn saved_rolls_stop_piling_up_at_the_most_a_hand_holds() {
+        let text = format!(
+            "{}rolls 9\ncarry-rolls\njokers NoJoker\ncolumns 1 1 1 1 1 1",
+            Ruleset::default()
+        );
+        let rules: Ruleset = text.parse().unwrap();
+        let mut game = Game::with_rules(&["Solo"], rules.clone(), Box::new(SeededDice::new(5)));
+        let mut turns = 0u32;
+        for column in 0..rules.columns().len() {
+            for category in rules.categories() {
+                // Score straight after the first roll, saving all the rest
+                game.reducer(FiveDiceMessage::RollDice).unwrap();
+                game.reducer(FiveDiceMessage::TakeScore(category, column))
+                    .unwrap();
+                turns += 1;
+                let saved = (9 + 8 * turns).min(u32::from(u8::MAX));
+                let rolls = game.active_player().hand().remaining_rolls;
+                assert_eq!(u32::from(rolls), saved);
+            }
+        }
+        assert_eq!(game.phase(), GamePhase::Finished);
+    }
+
+    #[test]
+    fn served_generala_wins_outright() {
+        // This seed serves the first player a Generala
+        let mut game = game_of(Variant::Generala, 2132);
+        game.reducer(FiveDiceMessage::AddPlayer).unwrap();
+        game.reducer(FiveDiceMessage::RollDice).unwrap();
+        assert!(game.active_player().hand().is_served());
+        game.reducer(FiveDiceMessage::TakeScore(ScoreType::AllFive, 0))
+            .unwrap();
+        assert_eq!(game.phase(), GamePhase::Finished);
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(game.standings()[0].1.name(), "Player 1");
+        // Taking it back takes the win back too
+        game.reducer(FiveDiceMessage::Undo).unwrap();
+        assert_eq!((game.phase(), game.winner()), (GamePhase::Playing, None));
+    }
+
+    #[test]
+    fn triple_fills_each_category_once_per_column() {
+        let mut game = game_of(Variant::Triple, 5);
+        assert_eq!(game.rounds(), 39);
+        game.reducer(FiveDiceMessage::RollDice).unwrap();
+        game.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 2))
+            .unwrap();
+        game.reducer(FiveDiceMessage::RollDice).unwrap();
+        assert_eq!(
+            game.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 3)),
+            Err(GameError::NoSuchColumn(3))
+        );
+        assert_eq!(
+            game.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 2)),
+            Err(GameError::SlotTaken(ScoreType::StoneSoup))
+        );
+        game.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 0))
+            .unwrap();
+        let score = game.players()[0].score();
+        assert!(score.is_open_in(1, ScoreType::StoneSoup));
+        assert_eq!(
+            score.total(),
+            score.column_total(0) + 3 * score.column_total(2)
+        );
+    }
+}
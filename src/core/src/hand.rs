@@ -0,0 +1,134 @@
+// hand.rs contains the dice rolled during a single turn
+
+use crate::dice::{DiceSource, Die, RollResult};
+use serde::{Deserialize, Serialize};
+
+// Number of dice in a standard turn
+pub const STANDARD_DICE: usize = 5;
+
+// Number of rolls in a standard turn
+pub const ROLLS_PER_TURN: u8 = 3;
+
+/// The dice for a single play - five of them unless the rules say otherwise
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{dice::SeededDice, hand::Hand};
+///
+/// let mut dice = SeededDice::new(3);
+/// let mut hand = Hand::new(&mut dice);
+/// assert_eq!(hand.throws(), 0);
+/// hand.roll(&mut dice);
+/// assert!(hand.is_served());
+/// hand.dice[0].toggle_held();
+/// hand.roll(&mut dice);
+/// assert_eq!(hand.throws(), 2);
+/// assert_eq!(hand.history().len(), 2);
+/// // The held die shows the same face after both throws
+/// assert_eq!(hand.history()[0][0], hand.history()[1][0]);
+/// assert_eq!(hand.history()[1], hand.values());
+///
+/// assert!(Hand::served(&[4, 4, 4, 4, 4]).unwrap().is_served());
+/// assert!(!Hand::from_values(&[4, 4, 4, 4, 4]).unwrap().is_served());
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hand {
+    pub dice: Vec<Die>,
+    pub remaining_rolls: u8,
+    // Rolls the turn started with
+    rolls: u8,
+    // The faces showing after each roll this turn, oldest first
+    history: Vec<Vec<u8>>,
+}
+
+impl Hand {
+    /// Roll a fresh standard hand with all its rolls remaining
+    pub fn new(dice: &mut dyn DiceSource) -> Self {
+        Self::with_size(STANDARD_DICE, ROLLS_PER_TURN, dice)
+    }
+
+    /// Roll a fresh hand of `size` dice for a turn of the given number of rolls
+    pub fn with_size(size: usize, rolls: u8, dice: &mut dyn DiceSource) -> Self {
+        Self {
+            dice: (0..size)
+                .map(|idx| Die::get_random(idx as u8, dice))
+                .collect(),
+            remaining_rolls: rolls,
+            rolls,
+            history: Vec::new(),
+        }
+    }
+
+    /// Build a hand showing the given faces after every roll of a standard turn, with none remaining
+    /// Returns None if any face is off a six-sided die
+    pub fn from_values(values: &[u8]) -> Option<Self> {
+        Self::after_throws(values, ROLLS_PER_TURN)
+    }
+
+    /// Build a hand showing the given faces on the first roll of a standard turn
+    /// Returns None if any face is off a six-sided die
+    pub fn served(values: &[u8]) -> Option<Self> {
+        Self::after_throws(values, 1)
+    }
+
+    /// A standard turn's hand showing the given faces after `throws` rolls
+    fn after_throws(values: &[u8], throws: u8) -> Option<Self> {
+        let dice = values
+            .iter()
+            .enumerate()
+            .map(|(idx, face)| Some(Die::new(idx as u8, RollResult::from_face(*face)?)))
+            .collect::<Option<Vec<Die>>>()?;
+        Some(Self {
+            dice,
+            remaining_rolls: ROLLS_PER_TURN - throws,
+            rolls: ROLLS_PER_TURN,
+            history: vec![values.to_vec(); throws as usize],
+        })
+    }
+
+    /// Whether the dice have been rolled at least once this turn
+    pub fn has_rolled(&self) -> bool {
+        self.remaining_rolls < self.rolls
+    }
+
+    /// Rolls this turn started with
+    pub fn rolls(&self) -> u8 {
+        self.rolls
+    }
+
+    /// How many times the dice have been rolled this turn - the faces showing came from the last
+    pub fn throws(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The faces showing after each roll this turn, oldest first
+    pub fn history(&self) -> &[Vec<u8>] {
+        &self.history
+    }
+
+    /// Whether the faces showing came from the turn's first roll
+    pub fn is_served(&self) -> bool {
+        self.throws() == 1
+    }
+
+    /// How many dice are in the hand
+    pub fn size(&self) -> usize {
+        self.dice.len()
+    }
+
+    /// The face value of each die
+    pub fn values(&self) -> Vec<u8> {
+        self.dice.iter().map(|die| die.value() as u8).collect()
+    }
+
+    /// all unheld dice if there are rolls left
+    pub fn roll(&mut self, dice: &mut dyn DiceSource) {
+        if self.remaining_rolls > 0 {
+            for die in self.dice.iter_mut() {
+                die.roll(dice);
+            }
+            self.remaining_rolls -= 1;
+            self.history.push(self.values());
+        }
+    }
+}
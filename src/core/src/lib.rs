@@ -0,0 +1,32 @@
+/// Computer players
+pub mod controller;
+/// Dice and where their rolls come from
+pub mod dice;
+/// Error type
+pub mod error;
+/// Players, turns and the game reducer
+pub mod game;
+/// A hand of dice for a single turn
+pub mod hand;
+/// Recording games and replaying them from their log
+pub mod log;
+/// Looking enum variants up by name, and stepping through them
+pub mod names;
+/// Exact chances of filling each category before the turn ends
+pub mod odds;
+/// High scores and lifetime stats kept across games
+pub mod records;
+/// Pricing every decision in a recorded game against optimal play
+pub mod review;
+/// Rulesets - categories, bonuses and dice - read from plain text
+pub mod rules;
+/// Saving games in progress and resuming them
+pub mod save;
+/// A player's scorecard
+pub mod score;
+/// Scoring rules for each category
+pub mod scoring;
+/// Playing batches of games with no one watching
+pub mod sim;
+/// The expected-value-optimal solitaire strategy
+pub mod solver;
@@ -0,0 +1,178 @@
+// log.rs records every message a game accepts and rebuilds games from the record
+
+use crate::{
+    dice::SeededDice,
+    error::{GameError, Result},
+    game::{FiveDiceMessage, Game},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Version written into every log - bump it whenever the format changes
+pub const LOG_VERSION: u32 = 1;
+
+// First word of every log, so other files are turned away early
+const LOG_MAGIC: &str = "fivedice-log";
+
+/// A whole game as the seed its dice started from plus each message it accepted, in order
+///
+/// Written out as plain text, one message per line, so it can be attached to a bug report
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     game::FiveDiceMessage,
+///     log::{GameLog, Recorder},
+///     scoring::ScoreType,
+/// };
+///
+/// let mut recorder = Recorder::new(1234);
+/// recorder.reducer(FiveDiceMessage::AddPlayer).unwrap();
+/// recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+/// recorder.reducer(FiveDiceMessage::HoldDie(2)).unwrap();
+/// recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+/// recorder.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 0)).unwrap();
+/// // Rejected messages change nothing, so they're left out
+/// assert!(recorder.reducer(FiveDiceMessage::HoldDie(9)).is_err());
+/// assert_eq!(recorder.log().messages().len(), 5);
+///
+/// let text = recorder.log().to_string();
+/// let log: GameLog = text.parse().unwrap();
+/// assert_eq!(&log, recorder.log());
+///
+/// let replayed = log.replay().unwrap();
+/// let original = recorder.game();
+/// assert_eq!(replayed.turn(), 1);
+/// assert_eq!(replayed.active_player().name(), original.active_player().name());
+/// assert_eq!(
+///     replayed.players()[0].score().total(),
+///     original.players()[0].score().total()
+/// );
+/// assert_eq!(
+///     replayed.active_player().hand().values(),
+///     original.active_player().hand().values()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameLog {
+    seed: u64,
+    messages: Vec<FiveDiceMessage>,
+}
+
+impl GameLog {
+    /// An empty log for a game whose dice start from this seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            messages: Vec::new(),
+        }
+    }
+
+    /// The seed the game's dice started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Every accepted message, oldest first
+    pub fn messages(&self) -> &[FiveDiceMessage] {
+        &self.messages
+    }
+
+    /// Append a message the reducer accepted
+    pub fn push(&mut self, msg: FiveDiceMessage) {
+        self.messages.push(msg);
+    }
+
+    /// Rebuild the game as it was after the first `count` messages
+    pub fn replay_to(&self, count: usize) -> Result<Game> {
+        let mut game = Game::new(Box::new(SeededDice::new(self.seed)));
+        for (idx, msg) in self.messages.iter().take(count).enumerate() {
+            // The header and seed come first, and lines count from 1
+            game.reducer(*msg).map_err(|_| GameError::BadLog(idx + 3))?;
+        }
+        Ok(game)
+    }
+
+    /// Rebuild the game exactly as it was after the last message
+    pub fn replay(&self) -> Result<Game> {
+        self.replay_to(self.messages.len())
+    }
+}
+
+impl fmt::Display for GameLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", LOG_MAGIC, LOG_VERSION)?;
+        writeln!(f, "seed {}", self.seed)?;
+        for msg in &self.messages {
+            writeln!(f, "{}", msg)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GameLog {
+    type Err = GameError;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.lines();
+        // Version header - only the current version is understood so far
+        let header = format!("{} {}", LOG_MAGIC, LOG_VERSION);
+        if lines.next().map(str::trim) != Some(header.as_str()) {
+            return Err(GameError::BadLog(1));
+        }
+        let seed = lines
+            .next()
+            .and_then(|line| line.trim().strip_prefix("seed "))
+            .and_then(|seed| seed.parse().ok())
+            .ok_or(GameError::BadLog(2))?;
+        let mut log = Self::new(seed);
+        for (idx, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let msg = FiveDiceMessage::from_line(line).ok_or(GameError::BadLog(idx + 3))?;
+            log.push(msg);
+        }
+        Ok(log)
+    }
+}
+
+/// A seeded game that logs every message it accepts
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recorder {
+    game: Game,
+    log: GameLog,
+}
+
+impl Recorder {
+    /// Start a fresh solo game with dice from this seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            game: Game::new(Box::new(SeededDice::new(seed))),
+            log: GameLog::new(seed),
+        }
+    }
+
+    /// The game as it stands
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Everything recorded so far
+    pub fn log(&self) -> &GameLog {
+        &self.log
+    }
+
+    /// Pass a message on to the game, recording it only if the rules allow it
+    pub fn reducer(&mut self, msg: FiveDiceMessage) -> Result<()> {
+        self.game.reducer(msg)?;
+        self.log.push(msg);
+        Ok(())
+    }
+
+    /// Pass a message on for one player, recording it only if it's theirs to make
+    pub fn reduce_as(&mut self, player: usize, msg: FiveDiceMessage) -> Result<()> {
+        self.game.reduce_as(player, msg)?;
+        self.log.push(msg);
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+// names.rs looks enum variants up by the names logs and rules files write, and steps through them
+
+use serde::de::{
+    value::{Error, StrDeserializer},
+    DeserializeOwned, IntoDeserializer,
+};
+
+/// The variant with this name, read through serde so a new variant is found with no list to
+/// keep up to date
+pub fn by_name<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let name: StrDeserializer<Error> = name.into_deserializer();
+    T::deserialize(name).ok()
+}
+
+/// Enums written out by their variant names, as in game logs and rules files
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{names::Named, scoring::JokerRule};
+///
+/// assert_eq!(JokerRule::from_name("FreeChoice"), Some(JokerRule::FreeChoice));
+/// assert_eq!(JokerRule::from_name(&format!("{:?}", JokerRule::NoJoker)), Some(JokerRule::NoJoker));
+/// assert_eq!(JokerRule::from_name("Sometimes"), None);
+/// ```
+pub trait Named: DeserializeOwned {
+    /// Look up a variant by its name
+    fn from_name(name: &str) -> Option<Self> {
+        by_name(name)
+    }
+}
+
+/// Enums a single button steps through in the UI
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{names::Cycle, scoring::JokerRule};
+///
+/// assert_eq!(JokerRule::Forced.next(), JokerRule::FreeChoice);
+/// assert_eq!(JokerRule::NoJoker.next(), JokerRule::Forced);
+/// ```
+pub trait Cycle: Copy + PartialEq + 'static {
+    /// Every variant, in the order the button steps through them
+    const ORDER: &'static [Self];
+
+    /// The next variant along, back round to the first after the last
+    fn next(self) -> Self {
+        let idx = Self::ORDER.iter().position(|v| *v == self);
+        Self::ORDER[idx.map_or(0, |idx| idx + 1) % Self::ORDER.len()]
+    }
+}
@@ -0,0 +1,134 @@
+// odds.rs works out the exact chance of filling each category with the rolls still to come
+
+use crate::{hand::Hand, rules::Ruleset, scoring::ScoreType, solver::Dice};
+
+// Without a bonus to aim for, three of a face is a fair upper box
+const UPPER_TARGET: usize = 3;
+
+/// How many of its face an upper box needs to count as filled
+/// The same number in every box that exactly reaches the first bonus threshold is the mark to hit
+fn upper_target(rules: &Ruleset) -> usize {
+    // Every face once over
+    const FACE_TOTAL: u16 = 21;
+    rules
+        .upper_bonus()
+        .first()
+        .map_or(UPPER_TARGET, |(threshold, _)| {
+            threshold.div_ceil(FACE_TOTAL) as usize
+        })
+}
+
+/// Whether a hand fills a category - upper boxes need `target` of their face to count
+fn makes(rules: &Ruleset, category: ScoreType, hand: &Hand, target: usize) -> bool {
+    match category.face() {
+        Some(face) => hand.values().iter().filter(|v| **v == face).count() >= target,
+        None => rules.makes(category, hand),
+    }
+}
+
+/// Exact chances of filling each category, holding the best dice for it on every roll to come
+///
+/// The next roll keeps whatever is already held - after that the holds are
+/// the ones that give the category its best chance
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{hand::Hand, odds::Odds, rules::Ruleset, scoring::ScoreType};
+///
+/// let odds = Odds::new(&Ruleset::default());
+/// let mut hand = Hand::from_values(&[1, 1, 1, 1, 1]).unwrap();
+///
+/// // A fresh turn - three rolls with nothing held
+/// hand.remaining_rolls = 3;
+/// assert!((odds.chance(ScoreType::AllFive, &hand) - 0.046029).abs() < 1e-6);
+///
+/// // A single roll of all five dice
+/// hand.remaining_rolls = 1;
+/// assert!((odds.chance(ScoreType::AllFive, &hand) - 6.0 / 7776.0).abs() < 1e-12);
+/// assert!((odds.chance(ScoreType::LgStraight, &hand) - 240.0 / 7776.0).abs() < 1e-12);
+/// assert!((odds.chance(ScoreType::TwoAndThree, &hand) - 300.0 / 7776.0).abs() < 1e-12);
+///
+/// // Holding four sixes with one roll left
+/// let mut hand = Hand::from_values(&[6, 6, 2, 6, 6]).unwrap();
+/// hand.remaining_rolls = 1;
+/// for idx in [0, 1, 3, 4].iter() {
+///     hand.dice[*idx].toggle_held();
+/// }
+/// assert!((odds.chance(ScoreType::AllFive, &hand) - 1.0 / 6.0).abs() < 1e-12);
+/// assert_eq!(odds.chance(ScoreType::FourKind, &hand), 1.0);
+///
+/// // With no rolls left it's down to the dice showing
+/// hand.remaining_rolls = 0;
+/// assert_eq!(odds.chance(ScoreType::Sixes, &hand), 1.0);
+/// assert_eq!(odds.chance(ScoreType::AllFive, &hand), 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Odds {
+    dice: Dice,
+    // Whether each full hand fills each category, as a chance of 0 or 1, in scorecard order
+    made: Vec<(ScoreType, Vec<f64>)>,
+}
+
+impl Default for Odds {
+    fn default() -> Self {
+        Self::new(&Ruleset::default())
+    }
+}
+
+impl Odds {
+    /// Work out which hands fill each category of these rules
+    pub fn new(rules: &Ruleset) -> Self {
+        let dice = Dice::new(rules.dice());
+        let target = upper_target(rules);
+        let made = rules
+            .categories()
+            .map(|category| {
+                let made = dice.counts[..dice.hands]
+                    .iter()
+                    .map(|c| {
+                        if makes(rules, category, &Dice::hand(c), target) {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect();
+                (category, made)
+            })
+            .collect();
+        Self { dice, made }
+    }
+
+    /// The chance this hand ends the turn filling the category
+    pub fn chance(&self, category: ScoreType, hand: &Hand) -> f64 {
+        let made = match self.made.iter().find(|(c, _)| *c == category) {
+            Some((_, made)) => made,
+            // The bonus box is never filled from a hand
+            None => return 0.0,
+        };
+        if hand.remaining_rolls == 0 {
+            return made[self.dice.index[&Dice::counts_of(&hand.values())]];
+        }
+        let mut hands = made.clone();
+        for _ in 1..hand.remaining_rolls {
+            hands = self.dice.hold_values(&self.dice.keep_values(&hands));
+        }
+        let keeps = self.dice.keep_values(&hands);
+        // Nothing is held going into the first roll
+        let held: Vec<u8> = hand
+            .dice
+            .iter()
+            .filter(|die| hand.has_rolled() && die.is_held())
+            .map(|die| die.value() as u8)
+            .collect();
+        keeps[self.dice.index[&Dice::counts_of(&held)]]
+    }
+
+    /// The chance of filling every category, in scorecard order
+    pub fn chances(&self, hand: &Hand) -> Vec<(ScoreType, f64)> {
+        self.made
+            .iter()
+            .map(|(category, _)| (*category, self.chance(*category, hand)))
+            .collect()
+    }
+}
@@ -0,0 +1,333 @@
+// records.rs keeps the high score table and each player's lifetime stats
+
+use crate::{
+    error::{GameError, Result},
+    game::{Game, Player},
+    save::{parse_versioned, Storage},
+    scoring::ScoreType,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+/// Version written into the records - bump it whenever the format changes
+pub const RECORDS_VERSION: u32 = 2;
+
+/// Where the records are kept
+pub const RECORDS_KEY: &str = "fivedice-records";
+
+/// How many entries each ruleset's high score table holds
+pub const HIGH_SCORE_LIMIT: usize = 10;
+
+/// A single finished scorecard that made the table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    /// When the game finished, as the caller's clock saw it
+    pub date: String,
+    pub total: u16,
+    /// Which rules the game was played under
+    pub ruleset: String,
+}
+
+/// Everything one player has done across all their finished games
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    games: u32,
+    points: u64,
+    // Games with a scored AllFive
+    all_fives: u32,
+    // Games that earned the upper section bonus
+    upper_bonuses: u32,
+    // Games where each category was filled with zero, in any column
+    scratches: HashMap<ScoreType, u32>,
+    // Hints asked for across every game
+    hints: u32,
+}
+
+impl PlayerStats {
+    /// Add a finished player
+    fn record(&mut self, player: &Player) {
+        let score = player.score();
+        self.games += 1;
+        self.hints += player.hints();
+        self.points += u64::from(score.total());
+        if score
+            .slots()
+            .iter()
+            .any(|slot| slot.category() == ScoreType::AllFive && slot.points() > 0)
+        {
+            self.all_fives += 1;
+        }
+        if score.upper_bonus() > 0 {
+            self.upper_bonuses += 1;
+        }
+        // A category scratched in several columns still counts once for the game
+        let scratched: HashSet<ScoreType> = score
+            .slots()
+            .iter()
+            .filter(|slot| slot.is_taken() && slot.points() == 0)
+            .map(|slot| slot.category())
+            .collect();
+        for category in scratched {
+            *self.scratches.entry(category).or_insert(0) += 1;
+        }
+    }
+
+    /// How much of the time something happened, as a fraction of games played
+    fn rate(&self, count: u32) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            f64::from(count) / f64::from(self.games)
+        }
+    }
+
+    /// Finished games played
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    /// Mean final total
+    pub fn average(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.points as f64 / f64::from(self.games)
+        }
+    }
+
+    /// Fraction of games with AllFive scored
+    pub fn all_five_rate(&self) -> f64 {
+        self.rate(self.all_fives)
+    }
+
+    /// Fraction of games that earned the upper bonus
+    pub fn upper_bonus_rate(&self) -> f64 {
+        self.rate(self.upper_bonuses)
+    }
+
+    /// Hints asked for per game on average
+    pub fn hints_per_game(&self) -> f64 {
+        self.rate(self.hints)
+    }
+
+    /// Fraction of games where this category was scratched
+    pub fn scratch_rate(&self, category: ScoreType) -> f64 {
+        self.rate(self.scratches.get(&category).copied().unwrap_or(0))
+    }
+}
+
+/// One ruleset's high score table and every player's stats under it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RulesetRecords {
+    // Best first, never longer than HIGH_SCORE_LIMIT
+    high_scores: Vec<HighScore>,
+    players: BTreeMap<String, PlayerStats>,
+}
+
+impl RulesetRecords {
+    /// The high score table, best first
+    pub fn high_scores(&self) -> &[HighScore] {
+        &self.high_scores
+    }
+
+    /// Stats for a single player, if they've finished a game under these rules
+    pub fn player(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    /// Every player with stats, by name
+    pub fn players(&self) -> impl Iterator<Item = (&str, &PlayerStats)> {
+        self.players
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+}
+
+/// The high score tables and every player's stats, kept in storage between games
+///
+/// Totals only compare under the same rules, so each ruleset keeps its own table and stats,
+/// going by its label
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     dice::SeededDice,
+///     game::{FiveDiceMessage, Game},
+///     records::Records,
+///     save::MemoryStorage,
+///     scoring::{JokerRule, ScoreType},
+/// };
+///
+/// let dice = Box::new(SeededDice::new(3));
+/// let mut game = Game::with_players(&["Ann"], JokerRule::FreeChoice, dice);
+/// // Hints only make sense once there's a roll to advise on
+/// assert!(game.reducer(FiveDiceMessage::Hint).is_err());
+/// for category in ScoreType::ALL.iter() {
+///     game.reducer(FiveDiceMessage::RollDice).unwrap();
+///     game.reducer(FiveDiceMessage::Hint).unwrap();
+///     game.reducer(FiveDiceMessage::TakeScore(*category, 0)).unwrap();
+/// }
+///
+/// let mut storage = MemoryStorage::new();
+/// let mut records = Records::load(&storage).unwrap();
+/// assert_eq!(records.record_game(&game, "2020-01-01"), vec![Some(1)]);
+/// records.store(&mut storage).unwrap();
+///
+/// let records = Records::load(&storage).unwrap();
+/// let label = game.rules().label();
+/// assert_eq!(records.rulesets().map(|(label, _)| label).collect::<Vec<_>>(), vec![&label]);
+/// let table = records.ruleset(&label).unwrap();
+/// let best = &table.high_scores()[0];
+/// assert_eq!(best.name, "Ann");
+/// assert_eq!(best.total, game.players()[0].score().total());
+/// // Games under other rules go in a table of their own
+/// assert!(records.ruleset("Triple Five Dice, Forced jokers").is_none());
+/// let stats = table.player("Ann").unwrap();
+/// assert_eq!(stats.games(), 1);
+/// assert_eq!(stats.average(), f64::from(best.total));
+/// assert_eq!(stats.hints_per_game(), 13.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Records {
+    // By ruleset label
+    rulesets: BTreeMap<String, RulesetRecords>,
+}
+
+#[derive(Serialize)]
+struct RecordsRef<'a> {
+    version: u32,
+    records: &'a Records,
+}
+
+#[derive(Deserialize)]
+struct RecordsFile {
+    records: Records,
+}
+
+impl Records {
+    /// The stored records, or empty ones if nothing has been stored yet
+    pub fn load(storage: &dyn Storage) -> Result<Self> {
+        match storage.get(RECORDS_KEY) {
+            Some(text) => {
+                let file: RecordsFile = parse_versioned(&text, RECORDS_VERSION)?;
+                Ok(file.records)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Write the records to storage, replacing what was there
+    pub fn store(&self, storage: &mut dyn Storage) -> Result<()> {
+        let text = serde_json::to_string(&RecordsRef {
+            version: RECORDS_VERSION,
+            records: self,
+        })
+        .map_err(|_| GameError::SaveFailed)?;
+        storage.set(RECORDS_KEY, &text)
+    }
+
+    /// The records for one ruleset, by its label, if a game has finished under it
+    pub fn ruleset(&self, label: &str) -> Option<&RulesetRecords> {
+        self.rulesets.get(label)
+    }
+
+    /// Every ruleset with a finished game, by label
+    pub fn rulesets(&self) -> impl Iterator<Item = (&str, &RulesetRecords)> {
+        self.rulesets
+            .iter()
+            .map(|(label, records)| (label.as_str(), records))
+    }
+
+    /// Add every scorecard from a finished game to its ruleset's records
+    /// Returns where each player landed on that ruleset's table, in player order
+    pub fn record_game(&mut self, game: &Game, date: &str) -> Vec<Option<usize>> {
+        let ruleset = game.rules().label();
+        let records = self.rulesets.entry(ruleset.clone()).or_default();
+        let mut entries = Vec::new();
+        for player in game.players() {
+            records
+                .players
+                .entry(player.name().into())
+                .or_default()
+                .record(player);
+            let entry = HighScore {
+                name: player.name().into(),
+                date: date.into(),
+                total: player.score().total(),
+                ruleset: ruleset.clone(),
+            };
+            records.high_scores.push(entry.clone());
+            entries.push(entry);
+        }
+        // Stable, so earlier scores keep their place on a tie
+        records
+            .high_scores
+            .sort_by_key(|entry| Reverse(entry.total));
+        records.high_scores.truncate(HIGH_SCORE_LIMIT);
+        // 1-indexed ranks, matching how the table is shown
+        entries
+            .iter()
+            .map(|entry| {
+                records
+                    .high_scores
+                    .iter()
+                    .position(|e| e == entry)
+                    .map(|idx| idx + 1)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Records;
+    use crate::{
+        dice::SeededDice,
+        game::{FiveDiceMessage, Game, GamePhase},
+        rules::Variant,
+        scoring::{JokerRule, ScoreType},
+    };
+
+    #[test]
+    fn a_category_scratched_in_several_columns_counts_once() {
+        let mut game = Game::new(Box::new(SeededDice::new(5)));
+        game.reducer(FiveDiceMessage::SetVariant(Variant::Triple))
+            .unwrap();
+        game.reducer(FiveDiceMessage::SetJokerRule(JokerRule::NoJoker))
+            .unwrap();
+        let categories: Vec<ScoreType> = game.rules().categories().collect();
+        // Take whatever the first roll gives, all the way down every column
+        for column in 0..3 {
+            for category in &categories {
+                game.reducer(FiveDiceMessage::RollDice).unwrap();
+                game.reducer(FiveDiceMessage::TakeScore(*category, column))
+                    .unwrap();
+            }
+        }
+        assert_eq!(game.phase(), GamePhase::Finished);
+        let score = game.players()[0].score();
+        let scratches = |category| {
+            score
+                .slots()
+                .iter()
+                .filter(|slot| slot.category() == category && slot.is_taken())
+                .filter(|slot| slot.points() == 0)
+                .count()
+        };
+        let repeated = categories
+            .iter()
+            .copied()
+            .find(|category| scratches(*category) > 1)
+            .unwrap();
+
+        let mut records = Records::default();
+        records.record_game(&game, "2026-10-16");
+        let label = game.rules().label();
+        let stats = records.ruleset(&label).unwrap().player("Player 1").unwrap();
+        assert_eq!(stats.scratch_rate(repeated), 1.0);
+    }
+}
@@ -0,0 +1,222 @@
+// review.rs goes back over a recorded game and prices every decision against optimal play
+
+use crate::{
+    dice::SeededDice,
+    error::{GameError, Result},
+    game::{FiveDiceMessage, Game},
+    log::GameLog,
+    scoring::ScoreType,
+    solver::Strategy,
+};
+
+// Tables are stored as f32, so anything closer than this is only rounding
+const TOLERANCE: f64 = 1e-3;
+
+/// What a player did, or should have done, at one point in a turn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Choice {
+    /// Roll again holding these dice, by position in the hand
+    Hold(Vec<bool>),
+    /// Score the hand in this category
+    Score(ScoreType),
+}
+
+/// A single hold or score choice, next to the best one on offer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    /// Who made it, by position at the table
+    pub player: usize,
+    pub round: usize,
+    /// The dice showing when it was made
+    pub dice: Vec<u8>,
+    pub remaining_rolls: u8,
+    pub chosen: Choice,
+    /// The final total the choice leads to on average
+    pub expected: f64,
+    pub best: Choice,
+    /// The final total the best choice leads to on average
+    pub best_expected: f64,
+}
+
+impl Decision {
+    /// Points given up on average by not making the best choice
+    pub fn cost(&self) -> f64 {
+        let cost = self.best_expected - self.expected;
+        if cost < TOLERANCE {
+            0.0
+        } else {
+            cost
+        }
+    }
+
+    /// Whether anything was given up at all
+    pub fn is_mistake(&self) -> bool {
+        self.cost() > 0.0
+    }
+}
+
+/// Rolling again - a hold decision, as long as there's a roll to hold for
+fn hold_decision(game: &Game, strategy: &Strategy) -> Option<Decision> {
+    let player = game.active_player();
+    let (score, hand) = (player.score(), player.hand());
+    let best = strategy.advise_hold(score, hand)?;
+    let held = hand.dice.iter().map(|die| die.is_held()).collect();
+    Some(Decision {
+        player: game.active(),
+        round: game.round(),
+        dice: hand.values(),
+        remaining_rolls: hand.remaining_rolls,
+        chosen: Choice::Hold(held),
+        expected: strategy.expected_hold(score, hand)?,
+        best: Choice::Hold(best.held),
+        best_expected: best.expected,
+    })
+}
+
+/// Taking a score - which also means giving up any rolls left
+fn score_decision(game: &Game, strategy: &Strategy, category: ScoreType) -> Option<Decision> {
+    let player = game.active_player();
+    let (score, hand) = (player.score(), player.hand());
+    let advice = strategy.advise_score(score, hand)?;
+    let mut best = (Choice::Score(advice.category), advice.expected);
+    // Holding everything is the same as scoring now, so only a real reroll can do better
+    if let Some(hold) = strategy.advise_hold(score, hand) {
+        if hold.expected > best.1 + TOLERANCE && !hold.held.iter().all(|held| *held) {
+            best = (Choice::Hold(hold.held), hold.expected);
+        }
+    }
+    Some(Decision {
+        player: game.active(),
+        round: game.round(),
+        dice: hand.values(),
+        remaining_rolls: hand.remaining_rolls,
+        chosen: Choice::Score(category),
+        expected: strategy.expected_score(score, hand, category)?,
+        best: best.0,
+        best_expected: best.1,
+    })
+}
+
+/// Every decision in a game that still stands, priced against the solver's strategy
+///
+/// Decisions taken back with Undo are left out, and only the game after the
+/// last StartOver is reviewed. Each player is judged as if playing solitaire.
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     game::FiveDiceMessage,
+///     log::Recorder,
+///     review::{Choice, Review},
+///     scoring::{JokerRule, ScoreType},
+///     solver::Strategy,
+/// };
+///
+/// // Fill the card in order, one roll each, up to the last round
+/// let mut recorder = Recorder::new(8);
+/// let rounds = recorder.game().rounds();
+/// for category in ScoreType::ALL.iter().take(rounds - 1) {
+///     recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+///     recorder.reducer(FiveDiceMessage::TakeScore(*category, 0)).unwrap();
+/// }
+/// // Tables for only what's left keep the example quick
+/// let score = recorder.game().active_player().score().clone();
+/// let strategy = Strategy::solve_from(&score, JokerRule::Forced);
+///
+/// // Reroll everything, then score with a roll still in hand
+/// recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+/// recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+/// recorder.reducer(FiveDiceMessage::TakeScore(ScoreType::StoneSoup, 0)).unwrap();
+///
+/// let review = Review::analyse(recorder.log(), &strategy).unwrap();
+/// let last: Vec<_> = review
+///     .decisions()
+///     .iter()
+///     .filter(|d| d.round == rounds)
+///     .collect();
+/// assert_eq!(last.len(), 2);
+/// assert_eq!(last[0].chosen, Choice::Hold(vec![false; 5]));
+/// assert_eq!(last[1].chosen, Choice::Score(ScoreType::StoneSoup));
+/// // Both threw points away - the second by not rolling again
+/// assert!(last.iter().all(|d| d.is_mistake()));
+/// assert!(matches!(last[1].best, Choice::Hold(_)));
+/// assert!((last[1].cost() - (last[1].best_expected - last[1].expected)).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Review {
+    decisions: Vec<Decision>,
+}
+
+impl Review {
+    /// Replay a log, pricing each hold and score as it's made
+    /// The strategy must be solved for the rules the game is played with
+    pub fn analyse(log: &GameLog, strategy: &Strategy) -> Result<Self> {
+        use FiveDiceMessage::*;
+        let mut game = Game::new(Box::new(SeededDice::new(log.seed())));
+        let mut decisions = Vec::new();
+        // Where each undoable move's decisions start, in step with the game's own history
+        let mut marks: Vec<usize> = Vec::new();
+        let mut undone: Vec<(usize, Vec<Decision>)> = Vec::new();
+        for (idx, msg) in log.messages().iter().enumerate() {
+            let decision = match msg {
+                RollDice | TakeScore(..) if !strategy.fits(game.rules()) => {
+                    return Err(GameError::RuleMismatch);
+                }
+                RollDice => hold_decision(&game, strategy),
+                TakeScore(category, _) => score_decision(&game, strategy, *category),
+                _ => None,
+            };
+            let mark = decisions.len();
+            // The header and seed come first, and lines count from 1
+            game.reducer(*msg).map_err(|_| GameError::BadLog(idx + 3))?;
+            match msg {
+                Undo => {
+                    if let Some(mark) = marks.pop() {
+                        undone.push((mark, decisions.split_off(mark)));
+                    }
+                }
+                Redo => {
+                    if let Some((mark, redone)) = undone.pop() {
+                        marks.push(mark);
+                        decisions.extend(redone);
+                    }
+                }
+                HoldDie(_) | TakeScore(..) => {
+                    marks.push(mark);
+                    undone.clear();
+                }
+                Hint => {}
+                StartOver => {
+                    decisions.clear();
+                    marks.clear();
+                    undone.clear();
+                }
+                _ => {
+                    marks.clear();
+                    undone.clear();
+                }
+            }
+            decisions.extend(decision);
+        }
+        Ok(Self { decisions })
+    }
+
+    /// Every decision, in the order they were made
+    pub fn decisions(&self) -> &[Decision] {
+        &self.decisions
+    }
+
+    /// Points given up on average across the whole game
+    pub fn equity_lost(&self) -> f64 {
+        self.decisions.iter().map(Decision::cost).sum()
+    }
+
+    /// Points given up on average by a single player
+    pub fn equity_lost_by(&self, player: usize) -> f64 {
+        self.decisions
+            .iter()
+            .filter(|d| d.player == player)
+            .map(Decision::cost)
+            .sum()
+    }
+}
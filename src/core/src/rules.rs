@@ -0,0 +1,599 @@
+// rules.rs holds everything a variant decides - its categories, how they score, and how the dice are thrown
+
+use crate::{
+    error::{GameError, Result},
+    hand::{Hand, ROLLS_PER_TURN, STANDARD_DICE},
+    names::{Cycle, Named},
+    scoring::{CategoryRule, JokerRule, Pattern, Points, ScoreType},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// The rules every game starts with, in the same text format anyone can write
+const STANDARD: &str = include_str!("../rules/standard.rules");
+
+// Scandinavian Yatzy
+const YATZY: &str = include_str!("../rules/yatzy.rules");
+
+// Maxi Yatzy, with six dice
+const MAXI_YATZY: &str = include_str!("../rules/maxi-yatzy.rules");
+
+// The standard rules over three weighted columns
+const TRIPLE: &str = include_str!("../rules/triple.rules");
+
+// Generala, with bonuses for hands served on the first roll
+const GENERALA: &str = include_str!("../rules/generala.rules");
+
+// Most dice a hand can hold - the odds tables grow quickly past this
+pub const MAX_DICE: usize = 6;
+
+// Most columns a scorecard can have, and the most a single column can count for
+// Kept small so weighted totals stay well inside a u16
+pub const MAX_COLUMNS: usize = 6;
+pub const MAX_WEIGHT: u16 = 9;
+
+/// The rulesets that come built in, to pick from at the start of a game
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     dice::SeededDice,
+///     error::GameError,
+///     game::{FiveDiceMessage, Game},
+///     rules::{Ruleset, Variant},
+///     scoring::ScoreType,
+/// };
+///
+/// assert_eq!(Variant::FiveDice.rules(), Ruleset::default());
+/// // Picked before the first score, like the joker rule
+/// let mut game = Game::new(Box::new(SeededDice::new(5)));
+/// game.reducer(FiveDiceMessage::SetVariant(Variant::Yatzy)).unwrap();
+/// assert_eq!(Variant::of(game.rules()), Some(Variant::Yatzy));
+/// assert_eq!(game.rounds(), 15);
+/// game.reducer(FiveDiceMessage::RollDice).unwrap();
+/// game.reducer(FiveDiceMessage::TakeScore(ScoreType::TwoPairs, 0)).unwrap();
+/// assert_eq!(
+///     game.reducer(FiveDiceMessage::SetVariant(Variant::FiveDice)),
+///     Err(GameError::AlreadyStarted)
+/// );
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Variant {
+    /// The standard rules, with jokers and the AllFive bonus
+    #[default]
+    FiveDice,
+    /// Scandinavian Yatzy - pairs, fixed straights and a bigger upper bonus
+    Yatzy,
+    /// Six dice, more categories, and unused rolls saved for later turns
+    MaxiYatzy,
+    /// The standard rules in three columns, counting once, twice and three times
+    Triple,
+    /// Generala - first roll bonuses, a double five of a kind, and a served one wins outright
+    Generala,
+}
+
+impl Variant {
+    /// Every built-in ruleset, in the order the UI cycles through them
+    pub const ALL: [Variant; 5] = [
+        Variant::FiveDice,
+        Variant::Yatzy,
+        Variant::MaxiYatzy,
+        Variant::Triple,
+        Variant::Generala,
+    ];
+
+    /// The rules this variant plays by
+    pub fn rules(self) -> Ruleset {
+        let text = match self {
+            Variant::FiveDice => STANDARD,
+            Variant::Yatzy => YATZY,
+            Variant::MaxiYatzy => MAXI_YATZY,
+            Variant::Triple => TRIPLE,
+            Variant::Generala => GENERALA,
+        };
+        text.parse().expect("the built-in rules are well formed")
+    }
+
+    /// The built-in variant these rules came from, going by name
+    pub fn of(rules: &Ruleset) -> Option<Self> {
+        Variant::ALL
+            .iter()
+            .find(|variant| variant.rules().name() == rules.name())
+            .copied()
+    }
+}
+
+impl Named for Variant {}
+
+impl Cycle for Variant {
+    const ORDER: &'static [Self] = &Variant::ALL;
+}
+
+/// A whole set of rules, written out as plain text so variants need no code
+///
+/// Each line is a setting, a `category`, a `#` comment or blank:
+///
+/// - `name TEXT`, `rolls N`, `jokers Forced|FreeChoice|NoJoker`
+/// - `dice N`, up to MAX_DICE and before any category
+/// - `carry-rolls`, to save rolls left unused for the player's next turn, up to 255 in all
+/// - `columns WEIGHT...`, for a card with a column per weight, each total counted that many
+///   times - up to MAX_COLUMNS columns of 1 to MAX_WEIGHT
+/// - `upper-bonus THRESHOLD POINTS`, once for each step of the bonus
+/// - `all-five-bonus POINTS`, for each five of a kind after a scored AllFive
+/// - `served-all-five-wins`, so a five of a kind on a turn's first roll ends the game in that
+///   player's favour once scored, in whichever category
+/// - `category NAME PATTERN`, in scorecard order, where the pattern is one of
+///   `face`, `chance`, `groups SIZE...`, `run LENGTH` or `faces FACE...`, and
+///   all but the first two end with the points - `sum`, `matched` or `fixed N` -
+///   optionally followed by `served N` for a bonus when filled on the first roll, then
+///   `requires NAME` for a category that only scores once an earlier one has scored points
+///   in the same column - the points are written out for `face` and `chance` too when either
+///   follows
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     hand::Hand,
+///     rules::{Ruleset, Variant},
+///     scoring::{JokerRule, ScoreType},
+/// };
+///
+/// let rules = Ruleset::default();
+/// assert_eq!(rules.rounds(), 13);
+/// assert_eq!(rules.joker_rule(), JokerRule::Forced);
+/// let hand = Hand::from_values(&[3, 3, 3, 5, 5]).unwrap();
+/// assert_eq!(rules.points(ScoreType::TwoAndThree, &hand), 25);
+/// // Written out, the rules read back the same
+/// for variant in Variant::ALL.iter() {
+///     let rules = variant.rules();
+///     assert_eq!(rules.to_string().parse::<Ruleset>().unwrap(), rules);
+/// }
+///
+/// let house: Ruleset = "
+///     name Pairs Only
+///     rolls 2
+///     columns 1 2
+///     upper-bonus 30 10
+///     category Sixes face
+///     category TwoAndThree groups 2 2 matched
+/// "
+/// .parse()
+/// .unwrap();
+/// assert_eq!(house.rounds(), 4);
+/// assert_eq!(house.columns(), &[1, 2]);
+/// assert_eq!(house.to_string().parse::<Ruleset>().unwrap(), house);
+/// assert_eq!(house.rolls(), 2);
+/// assert_eq!(house.joker_rule(), JokerRule::NoJoker);
+/// assert_eq!(house.points(ScoreType::TwoAndThree, &hand), 16);
+/// assert_eq!(house.points(ScoreType::ThreeKind, &hand), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruleset {
+    name: String,
+    dice: usize,
+    rolls: u8,
+    // Whether unused rolls are saved for the next turn
+    carry_rolls: bool,
+    // What each scorecard column's total is multiplied by, one weight per column
+    columns: Vec<u16>,
+    joker_rule: JokerRule,
+    // Upper subtotals that earn a bonus and what each earns, lowest first - the highest reached counts
+    upper_bonus: Vec<(u16, u16)>,
+    // Points for each five of a kind after AllFive is scored, or 0 for none
+    all_five_bonus: u16,
+    // Whether a five of a kind on the first roll wins the game outright
+    served_all_five_wins: bool,
+    // In scorecard order
+    categories: Vec<CategoryRule>,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Variant::FiveDice.rules()
+    }
+}
+
+impl Ruleset {
+    /// What the rules are called
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many dice make a hand
+    pub fn dice(&self) -> usize {
+        self.dice
+    }
+
+    /// How many rolls each turn gets
+    pub fn rolls(&self) -> u8 {
+        self.rolls
+    }
+
+    /// Whether rolls left unused are saved for the player's next turn
+    pub fn carries_rolls(&self) -> bool {
+        self.carry_rolls
+    }
+
+    /// The weight of each scorecard column, left to right
+    pub fn columns(&self) -> &[u16] {
+        &self.columns
+    }
+
+    /// How a five of a kind may be placed once AllFive is filled
+    pub fn joker_rule(&self) -> JokerRule {
+        self.joker_rule
+    }
+
+    /// The same rules with a different joker policy
+    pub fn with_joker_rule(mut self, rule: JokerRule) -> Self {
+        self.joker_rule = rule;
+        self
+    }
+
+    pub(crate) fn set_joker_rule(&mut self, rule: JokerRule) {
+        self.joker_rule = rule;
+    }
+
+    /// The steps of the upper section bonus, as (threshold, points), lowest first
+    pub fn upper_bonus(&self) -> &[(u16, u16)] {
+        &self.upper_bonus
+    }
+
+    /// Points for each five of a kind after a scored AllFive
+    pub fn all_five_bonus(&self) -> u16 {
+        self.all_five_bonus
+    }
+
+    /// Whether scoring a five of a kind from the turn's first roll wins the game outright
+    pub fn served_all_five_wins(&self) -> bool {
+        self.served_all_five_wins
+    }
+
+    /// Every category, in scorecard order
+    pub fn categories(&self) -> impl Iterator<Item = ScoreType> + '_ {
+        self.categories.iter().map(|rule| rule.category)
+    }
+
+    /// How a single category is filled and scored, if it's part of these rules
+    pub fn rule(&self, category: ScoreType) -> Option<&CategoryRule> {
+        self.categories
+            .iter()
+            .find(|rule| rule.category == category)
+    }
+
+    /// Turns each player takes - one per category in each column
+    pub fn rounds(&self) -> usize {
+        self.categories.len() * self.columns.len()
+    }
+
+    /// Whether the hand fills a category for more than a scratch
+    pub fn makes(&self, category: ScoreType, hand: &Hand) -> bool {
+        self.rule(category).is_some_and(|rule| rule.makes(hand))
+    }
+
+    /// The points a hand is worth in a category - 0 if it doesn't qualify
+    pub fn points(&self, category: ScoreType, hand: &Hand) -> u16 {
+        self.rule(category).map_or(0, |rule| rule.score(hand))
+    }
+
+    /// The points a hand is worth in a category when played as a joker
+    pub fn joker_points(&self, category: ScoreType, hand: &Hand) -> u16 {
+        self.rule(category).map_or(0, |rule| rule.joker_score(hand))
+    }
+
+    /// A short description for tables, naming the joker policy where there's a choice
+    pub fn label(&self) -> String {
+        if self.all_five_bonus > 0 {
+            format!("{}, {:?} jokers", self.name, self.joker_rule)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// The pattern's own points, when a line leaves them off
+fn implied_points(pattern: &Pattern) -> Option<Points> {
+    match pattern {
+        Pattern::Face => Some(Points::Matched),
+        Pattern::Chance => Some(Points::Sum),
+        _ => None,
+    }
+}
+
+/// Numbers up to the points word, then the points themselves
+fn numbers_then_points<'a>(
+    words: &mut impl Iterator<Item = &'a str>,
+) -> Option<(Vec<u8>, Option<Points>)> {
+    let mut numbers = Vec::new();
+    for word in words.by_ref() {
+        let points = match word {
+            "sum" => Points::Sum,
+            "matched" => Points::Matched,
+            "fixed" => Points::Fixed(words.next()?.parse().ok()?),
+            number => {
+                numbers.push(number.parse().ok()?);
+                continue;
+            }
+        };
+        return Some((numbers, Some(points)));
+    }
+    Some((numbers, None))
+}
+
+/// A category line past the `category` word, if it makes sense for this many dice
+fn parse_category<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+    dice: usize,
+) -> Option<CategoryRule> {
+    let category = ScoreType::from_name(words.next()?)?;
+    let kind = words.next()?;
+    let (numbers, points) = numbers_then_points(&mut words)?;
+    let pattern = match kind {
+        "face" if numbers.is_empty() && category.is_upper() => Pattern::Face,
+        "chance" if numbers.is_empty() => Pattern::Chance,
+        "groups"
+            if !numbers.is_empty()
+                && !numbers.contains(&0)
+                && numbers
+                    .iter()
+                    .try_fold(0u8, |sum, size| sum.checked_add(*size))
+                    .is_some_and(|sum| sum as usize <= dice) =>
+        {
+            Pattern::Groups(numbers)
+        }
+        "run" if numbers.len() == 1 && (1..=6).contains(&numbers[0]) => Pattern::Run(numbers[0]),
+        "faces"
+            if !numbers.is_empty()
+                && numbers.len() <= dice
+                && numbers.iter().all(|face| (1..=6).contains(face)) =>
+        {
+            Pattern::Faces(numbers)
+        }
+        _ => return None,
+    };
+    let points = points.or_else(|| implied_points(&pattern))?;
+    let mut next = words.next();
+    let served = match next {
+        Some("served") => {
+            let served = words.next()?.parse().ok()?;
+            next = words.next();
+            served
+        }
+        _ => 0,
+    };
+    let requires = match next {
+        Some("requires") => {
+            let required = ScoreType::from_name(words.next()?).filter(|r| *r != category)?;
+            next = words.next();
+            Some(required)
+        }
+        _ => None,
+    };
+    // The most the category can ever pay, bonus included, has to fit
+    let most = match points {
+        Points::Fixed(points) => points,
+        _ => 6 * dice as u16,
+    };
+    most.checked_add(served)?;
+    // Nothing may trail the points, or the served bonus and requirement after them
+    match next {
+        Some(_) => None,
+        None => Some(CategoryRule {
+            category,
+            pattern,
+            points,
+            served,
+            requires,
+        }),
+    }
+}
+
+impl FromStr for Ruleset {
+    type Err = GameError;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut ret = Self {
+            name: "Custom".into(),
+            dice: STANDARD_DICE,
+            rolls: ROLLS_PER_TURN,
+            carry_rolls: false,
+            columns: vec![1],
+            joker_rule: JokerRule::NoJoker,
+            upper_bonus: Vec::new(),
+            all_five_bonus: 0,
+            served_all_five_wins: false,
+            categories: Vec::new(),
+        };
+        let mut last = 0;
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            last = idx + 1;
+            let bad = GameError::BadRules(idx + 1);
+            let mut words = line.split_whitespace();
+            let setting = words.next().ok_or(bad)?;
+            let mut number = || words.next().and_then(|w| w.parse::<u16>().ok()).ok_or(bad);
+            match setting {
+                "name" => ret.name = line["name".len()..].trim().into(),
+                // Categories are checked against the dice, so those come first
+                "dice" if ret.categories.is_empty() => match number()? as usize {
+                    dice @ 1..=MAX_DICE => ret.dice = dice,
+                    _ => return Err(bad),
+                },
+                "rolls" => match number()? {
+                    rolls @ 1..=9 => ret.rolls = rolls as u8,
+                    _ => return Err(bad),
+                },
+                "carry-rolls" => ret.carry_rolls = true,
+                "columns" => {
+                    let weights: Option<Vec<u16>> = words
+                        .by_ref()
+                        .map(|w| w.parse().ok().filter(|w| (1..=MAX_WEIGHT).contains(w)))
+                        .collect();
+                    ret.columns = weights
+                        .filter(|w| (1..=MAX_COLUMNS).contains(&w.len()))
+                        .ok_or(bad)?;
+                }
+                "jokers" => {
+                    ret.joker_rule = words.next().and_then(JokerRule::from_name).ok_or(bad)?;
+                }
+                "upper-bonus" => {
+                    let threshold = number()?;
+                    let points = number()?;
+                    ret.upper_bonus.push((threshold, points));
+                    ret.upper_bonus.sort_unstable();
+                }
+                "all-five-bonus" => ret.all_five_bonus = number()?,
+                "served-all-five-wins" => ret.served_all_five_wins = true,
+                "category" => {
+                    let rule = parse_category(words.by_ref(), ret.dice).ok_or(bad)?;
+                    // A required category has to be listed first
+                    let unlisted = rule.requires.is_some_and(|r| ret.rule(r).is_none());
+                    if ret.rule(rule.category).is_some() || unlisted {
+                        return Err(bad);
+                    }
+                    ret.categories.push(rule);
+                    continue;
+                }
+                _ => return Err(bad),
+            }
+            // Nothing may trail a setting, apart from the words of a name
+            if setting != "name" && words.next().is_some() {
+                return Err(bad);
+            }
+        }
+        if ret.categories.is_empty() {
+            return Err(GameError::BadRules(last.max(1)));
+        }
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for Points {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Points::Sum => write!(f, "sum"),
+            Points::Matched => write!(f, "matched"),
+            Points::Fixed(points) => write!(f, "fixed {}", points),
+        }
+    }
+}
+
+impl fmt::Display for CategoryRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let numbers = |numbers: &[u8]| {
+            numbers
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        write!(f, "category {:?} ", self.category)?;
+        match &self.pattern {
+            Pattern::Face => write!(f, "face")?,
+            Pattern::Chance => write!(f, "chance")?,
+            Pattern::Groups(sizes) => write!(f, "groups {}", numbers(sizes))?,
+            Pattern::Run(length) => write!(f, "run {}", length)?,
+            Pattern::Faces(faces) => write!(f, "faces {}", numbers(faces))?,
+        }
+        let trailed = self.served > 0 || self.requires.is_some();
+        if implied_points(&self.pattern) != Some(self.points) || trailed {
+            write!(f, " {}", self.points)?;
+        }
+        if self.served > 0 {
+            write!(f, " served {}", self.served)?;
+        }
+        if let Some(required) = self.requires {
+            write!(f, " requires {:?}", required)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "name {}", self.name)?;
+        writeln!(f, "dice {}", self.dice)?;
+        writeln!(f, "rolls {}", self.rolls)?;
+        if self.carry_rolls {
+            writeln!(f, "carry-rolls")?;
+        }
+        if self.columns != [1] {
+            let weights: Vec<String> = self.columns.iter().map(|w| w.to_string()).collect();
+            writeln!(f, "columns {}", weights.join(" "))?;
+        }
+        writeln!(f, "jokers {:?}", self.joker_rule)?;
+        for (threshold, points) in &self.upper_bonus {
+            writeln!(f, "upper-bonus {} {}", threshold, points)?;
+        }
+        if self.all_five_bonus > 0 {
+            writeln!(f, "all-five-bonus {}", self.all_five_bonus)?;
+        }
+        if self.served_all_five_wins {
+            writeln!(f, "served-all-five-wins")?;
+        }
+        for rule in &self.categories {
+            writeln!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ruleset;
+    use crate::error::GameError;
+
+    /// The line a ruleset is turned down at, if it is
+    fn rejected_at(text: &str) -> Option<usize> {
+        match text.parse::<Ruleset>() {
+            Err(GameError::BadRules(line)) => Some(line),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn unknown_categories_and_empty_rulesets_are_rejected() {
+        assert_eq!(rejected_at("category Sevens face"), Some(1));
+        assert_eq!(rejected_at("rolls 3"), Some(1));
+    }
+
+    #[test]
+    fn categories_that_could_overflow_or_match_anything_are_rejected() {
+        assert_eq!(
+            rejected_at("category ThreeKind groups 200 200 sum"),
+            Some(1)
+        );
+        assert_eq!(
+            rejected_at("category AllFive groups 5 fixed 65000 served 1000"),
+            Some(1)
+        );
+        // Empty groups would fit any hand at all
+        assert_eq!(rejected_at("category ThreeKind groups 0 sum"), Some(1));
+        assert_eq!(
+            rejected_at("category TwoAndThree groups 3 0 0 fixed 25"),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn column_weights_have_to_be_in_range() {
+        assert_eq!(rejected_at("columns 1 0\ncategory Ones face"), Some(1));
+        assert_eq!(rejected_at("columns 60000\ncategory Ones face"), Some(1));
+        assert_eq!(
+            rejected_at("columns 1 1 1 1 1 1 1\ncategory Ones face"),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn a_required_category_has_to_come_first() {
+        let backwards = "category StoneSoup chance sum requires Ones\ncategory Ones face";
+        assert_eq!(rejected_at(backwards), Some(1));
+        let itself = "category StoneSoup chance sum requires StoneSoup";
+        assert_eq!(rejected_at(itself), Some(1));
+        let forwards = "category Ones face\ncategory StoneSoup chance sum requires Ones";
+        assert_eq!(rejected_at(forwards), None);
+    }
+}
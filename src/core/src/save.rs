@@ -0,0 +1,132 @@
+// save.rs writes a game in progress out to storage and picks it back up
+
+use crate::{
+    error::{GameError, Result},
+    log::Recorder,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version written into every save - bump it whenever the format changes
+pub const SAVE_VERSION: u32 = 4;
+
+/// Where the game in progress is kept
+pub const SAVE_KEY: &str = "fivedice-save";
+
+/// Somewhere to keep strings by key, like the browser's localStorage
+pub trait Storage {
+    /// The value stored under this key, if any
+    fn get(&self, key: &str) -> Option<String>;
+    /// Store a value, replacing anything already under this key
+    fn set(&mut self, key: &str, value: &str) -> Result<()>;
+}
+
+/// Storage that lives only as long as the program - for native tests and tools
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStorage {
+    values: HashMap<String, String>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        self.values.insert(key.into(), value.into());
+        Ok(())
+    }
+}
+
+// Just enough of a stored file to know how to read the rest of it
+#[derive(Deserialize)]
+struct VersionHeader {
+    version: u32,
+}
+
+/// Parse a stored file, turning down any version but the one this build writes
+pub(crate) fn parse_versioned<T: DeserializeOwned>(text: &str, expected: u32) -> Result<T> {
+    let header: VersionHeader = serde_json::from_str(text).map_err(|_| GameError::BadSave)?;
+    if header.version != expected {
+        return Err(GameError::UnsupportedSave(header.version));
+    }
+    serde_json::from_str(text).map_err(|_| GameError::BadSave)
+}
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u32,
+    recorder: &'a Recorder,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    recorder: Recorder,
+}
+
+/// Write the game and its log to storage, replacing any earlier save
+pub fn save(storage: &mut dyn Storage, recorder: &Recorder) -> Result<()> {
+    let text = serde_json::to_string(&SaveRef {
+        version: SAVE_VERSION,
+        recorder,
+    })
+    .map_err(|_| GameError::SaveFailed)?;
+    storage.set(SAVE_KEY, &text)
+}
+
+/// Pick the saved game back up, if there is one
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     error::GameError,
+///     game::FiveDiceMessage,
+///     log::Recorder,
+///     save::{load, save, MemoryStorage, Storage, SAVE_KEY},
+/// };
+///
+/// let mut storage = MemoryStorage::new();
+/// assert!(load(&storage).unwrap().is_none());
+///
+/// let mut recorder = Recorder::new(99);
+/// recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+/// recorder.reducer(FiveDiceMessage::HoldDie(0)).unwrap();
+/// save(&mut storage, &recorder).unwrap();
+///
+/// // Resumed games carry on rolling exactly as the original would
+/// let mut resumed = load(&storage).unwrap().unwrap();
+/// assert_eq!(resumed.log(), recorder.log());
+/// assert!(resumed.game().active_player().hand().dice[0].is_held());
+/// resumed.reducer(FiveDiceMessage::RollDice).unwrap();
+/// recorder.reducer(FiveDiceMessage::RollDice).unwrap();
+/// assert_eq!(
+///     resumed.game().active_player().hand().values(),
+///     recorder.game().active_player().hand().values()
+/// );
+///
+/// // Saves from an unknown version are turned down rather than misread
+/// storage.set(SAVE_KEY, r#"{"version":0}"#).unwrap();
+/// assert_eq!(load(&storage).unwrap_err(), GameError::UnsupportedSave(0));
+/// storage.set(SAVE_KEY, "not a save").unwrap();
+/// assert_eq!(load(&storage).unwrap_err(), GameError::BadSave);
+/// ```
+pub fn load(storage: &dyn Storage) -> Result<Option<Recorder>> {
+    let text = match storage.get(SAVE_KEY) {
+        Some(text) => text,
+        None => return Ok(None),
+    };
+    let file: SaveFile = parse_versioned(&text, SAVE_VERSION)?;
+    let recorder = file.recorder;
+    // Guard against hand-edited saves the reducer would trip over
+    let game = recorder.game();
+    if game.players().is_empty() || game.active() >= game.players().len() {
+        return Err(GameError::BadSave);
+    }
+    Ok(Some(recorder))
+}
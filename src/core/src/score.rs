@@ -0,0 +1,473 @@
+// score.rs contains a single player's scorecard
+
+use crate::{
+    hand::Hand,
+    rules::Ruleset,
+    scoring::{is_all_five, JokerRule, ScoreType},
+};
+use serde::{Deserialize, Serialize};
+
+/// A single score option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ScoreSlot {
+    taken: bool,
+    value: ScoreType,
+    points: u16,
+    column: usize,
+}
+
+impl ScoreSlot {
+    fn new(value: ScoreType, column: usize) -> Self {
+        Self {
+            taken: false,
+            value,
+            points: 0,
+            column,
+        }
+    }
+
+    /// The category this slot holds
+    pub fn category(&self) -> ScoreType {
+        self.value
+    }
+
+    /// Points locked in so far, before the column's weight
+    pub fn points(&self) -> u16 {
+        self.points
+    }
+
+    /// Which column of the card this slot sits in, from 0
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Whether a score has been locked in
+    pub fn is_taken(&self) -> bool {
+        self.taken
+    }
+
+    /// Whether a player can still choose this slot
+    pub fn is_open(&self) -> bool {
+        !self.taken && self.value != ScoreType::AllFiveBonus
+    }
+}
+
+/// A single player's score object
+///
+/// Most rules give a card one column, but some fill several at once, each counting a
+/// multiple of its own total
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{hand::Hand, rules::Variant, score::Score, scoring::ScoreType};
+///
+/// let rules = Variant::Triple.rules();
+/// let mut score = Score::for_rules(&rules);
+/// assert_eq!(score.columns(), 3);
+/// let hand = Hand::from_values(&[6, 6, 6, 2, 2]).unwrap();
+/// for column in 0..3 {
+///     assert!(score.can_take(column, ScoreType::Sixes, &hand, &rules));
+/// }
+/// score.take_in(2, ScoreType::Sixes, score.points_for(2, ScoreType::Sixes, &hand, &rules));
+/// score.take_in(0, ScoreType::TwoAndThree, 25);
+/// assert!(score.is_open_in(0, ScoreType::Sixes));
+/// assert!(!score.can_take(2, ScoreType::Sixes, &hand, &rules));
+/// assert_eq!(score.column_total(2), 18);
+/// // Each column counts its weight times over
+/// assert_eq!(score.total(), 25 + 3 * 18);
+/// assert_eq!(score.upper_subtotal(), 54);
+/// assert_eq!(score.lower_subtotal(), 25);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    // Kept in scorecard order, a column at a time
+    slots: Vec<ScoreSlot>,
+    // The rules' upper bonus steps, as (threshold, points), lowest first
+    upper_bonus: Vec<(u16, u16)>,
+    // What each column's total is multiplied by
+    weights: Vec<u16>,
+}
+
+impl Score {
+    /// A blank card for the standard rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A blank card with a slot for every category the rules have, in every column
+    pub fn for_rules(rules: &Ruleset) -> Self {
+        let mut slots = Vec::new();
+        for column in 0..rules.columns().len() {
+            slots.extend(rules.categories().map(|c| ScoreSlot::new(c, column)));
+            if rules.all_five_bonus() > 0 {
+                slots.push(ScoreSlot::new(ScoreType::AllFiveBonus, column));
+            }
+        }
+        Self {
+            slots,
+            upper_bonus: rules.upper_bonus().to_vec(),
+            weights: rules.columns().to_vec(),
+        }
+    }
+
+    /// Every slot, in scorecard order, a column at a time
+    pub fn slots(&self) -> &[ScoreSlot] {
+        &self.slots
+    }
+
+    /// How many columns the card has
+    pub fn columns(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// What a column's total is multiplied by - 0 past the last column
+    pub fn weight(&self, column: usize) -> u16 {
+        self.weights.get(column).copied().unwrap_or(0)
+    }
+
+    /// The slots of a single column, in scorecard order
+    pub fn column_slots(&self, column: usize) -> impl Iterator<Item = &ScoreSlot> + '_ {
+        self.slots.iter().filter(move |slot| slot.column == column)
+    }
+
+    /// Whether the given category is still open in the first column
+    pub fn is_open(&self, category: ScoreType) -> bool {
+        self.is_open_in(0, category)
+    }
+
+    /// Whether the given category is still open in a column
+    pub fn is_open_in(&self, column: usize, category: ScoreType) -> bool {
+        self.slot_in(column, category)
+            .is_some_and(ScoreSlot::is_open)
+    }
+
+    /// Get the slot for a category in the first column
+    pub fn slot(&self, category: ScoreType) -> Option<&ScoreSlot> {
+        self.slot_in(0, category)
+    }
+
+    /// Get the slot for a category in a column
+    pub fn slot_in(&self, column: usize, category: ScoreType) -> Option<&ScoreSlot> {
+        self.column_slots(column)
+            .find(|slot| slot.value == category)
+    }
+
+    /// Whether this hand is played as a joker in a column - a five of a kind with that
+    /// column's AllFive already filled
+    pub fn is_joker(&self, column: usize, hand: &Hand, rules: &Ruleset) -> bool {
+        rules.joker_rule() != JokerRule::NoJoker
+            && is_all_five(hand)
+            && self
+                .slot_in(column, ScoreType::AllFive)
+                .is_some_and(ScoreSlot::is_taken)
+    }
+
+    /// Whether the given category may be chosen in a column for this hand under the joker rule
+    pub fn can_take(
+        &self,
+        column: usize,
+        category: ScoreType,
+        hand: &Hand,
+        rules: &Ruleset,
+    ) -> bool {
+        if !self.is_open_in(column, category) {
+            return false;
+        }
+        if rules.joker_rule() != JokerRule::Forced || !self.is_joker(column, hand, rules) {
+            return true;
+        }
+        // Forced jokers must use the matching upper box first, then any lower box
+        let upper = ScoreType::from_face(hand.values()[0]).unwrap();
+        if self.is_open_in(column, upper) {
+            category == upper
+        } else if self
+            .column_slots(column)
+            .any(|slot| !slot.value.is_upper() && slot.is_open())
+        {
+            !category.is_upper()
+        } else {
+            true
+        }
+    }
+
+    /// The points this hand would score in a column's category under the joker rule,
+    /// before the column's weight
+    /// A category that requires another is only a scratch until that one has scored
+    pub fn points_for(
+        &self,
+        column: usize,
+        category: ScoreType,
+        hand: &Hand,
+        rules: &Ruleset,
+    ) -> u16 {
+        let unmet = rules
+            .rule(category)
+            .and_then(|rule| rule.requires)
+            .is_some_and(|required| {
+                self.slot_in(column, required)
+                    .is_none_or(|slot| slot.points == 0)
+            });
+        if unmet {
+            0
+        } else if self.is_joker(column, hand, rules) {
+            rules.joker_points(category, hand)
+        } else {
+            rules.points(category, hand)
+        }
+    }
+
+    /// Lock in points for a category in the first column - no action if it's already taken
+    pub fn take(&mut self, category: ScoreType, points: u16) {
+        self.take_in(0, category, points);
+    }
+
+    /// Lock in points for a category in a column - no action if it's already taken
+    pub fn take_in(&mut self, column: usize, category: ScoreType, points: u16) {
+        if let Some(slot) = self
+            .slots
+            .iter_mut()
+            .find(|slot| slot.column == column && slot.value == category && slot.is_open())
+        {
+            slot.taken = true;
+            slot.points = points;
+        }
+    }
+
+    /// Award the bonus for an extra five of a kind, if the column's AllFive was scored for points
+    pub fn award_all_five_bonus(&mut self, column: usize, hand: &Hand, rules: &Ruleset) {
+        let earned = self
+            .slot_in(column, ScoreType::AllFive)
+            .is_some_and(|slot| slot.taken && slot.points > 0);
+        if earned && is_all_five(hand) {
+            if let Some(slot) = self
+                .slots
+                .iter_mut()
+                .find(|slot| slot.column == column && slot.value == ScoreType::AllFiveBonus)
+            {
+                slot.points = slot.points.saturating_add(rules.all_five_bonus());
+            }
+        }
+    }
+
+    /// Whether every category has been filled
+    pub fn is_complete(&self) -> bool {
+        !self.slots.iter().any(|slot| slot.is_open())
+    }
+
+    /// Sum of the points taken in a column's upper or lower section - hand-written rules can pay
+    /// more than a u16 holds, so it tops out rather than wrapping
+    fn section_subtotal(&self, column: usize, upper: bool) -> u16 {
+        self.column_slots(column)
+            .filter(|slot| slot.value.is_upper() == upper)
+            .fold(0, |sum: u16, slot| sum.saturating_add(slot.points))
+    }
+
+    /// Sum of every column's figure, each times its weight - topping out rather than wrapping
+    fn weighted(&self, figure: impl Fn(usize) -> u16) -> u16 {
+        self.weights
+            .iter()
+            .enumerate()
+            .fold(0, |sum: u16, (column, weight)| {
+                sum.saturating_add(weight.saturating_mul(figure(column)))
+            })
+    }
+
+    /// Sum of Ones through Sixes in a column
+    pub fn column_upper_subtotal(&self, column: usize) -> u16 {
+        self.section_subtotal(column, true)
+    }
+
+    /// A column's upper section bonus, for the highest threshold its subtotal has reached
+    pub fn column_upper_bonus(&self, column: usize) -> u16 {
+        let subtotal = self.column_upper_subtotal(column);
+        self.upper_bonus
+            .iter()
+            .filter(|(threshold, _)| subtotal >= *threshold)
+            .map(|(_, points)| *points)
+            .next_back()
+            .unwrap_or(0)
+    }
+
+    /// How many more upper section points a column needs to earn the next step of the bonus
+    pub fn bonus_needed(&self, column: usize) -> u16 {
+        let subtotal = self.column_upper_subtotal(column);
+        self.upper_bonus
+            .iter()
+            .find(|(threshold, _)| *threshold > subtotal)
+            .map_or(0, |(threshold, _)| threshold - subtotal)
+    }
+
+    /// Sum of everything below the upper section in a column
+    pub fn column_lower_subtotal(&self, column: usize) -> u16 {
+        self.section_subtotal(column, false)
+    }
+
+    /// A column's total, including its upper bonus, before its weight
+    pub fn column_total(&self, column: usize) -> u16 {
+        self.column_upper_subtotal(column)
+            .saturating_add(self.column_upper_bonus(column))
+            .saturating_add(self.column_lower_subtotal(column))
+    }
+
+    /// Sum of Ones through Sixes, weighted across the columns
+    pub fn upper_subtotal(&self) -> u16 {
+        self.weighted(|column| self.column_upper_subtotal(column))
+    }
+
+    /// The upper section bonuses, weighted across the columns
+    pub fn upper_bonus(&self) -> u16 {
+        self.weighted(|column| self.column_upper_bonus(column))
+    }
+
+    /// Sum of everything below the upper section, weighted across the columns
+    pub fn lower_subtotal(&self) -> u16 {
+        self.weighted(|column| self.column_lower_subtotal(column))
+    }
+
+    /// Grand total, including the upper bonuses, weighted across the columns
+    pub fn total(&self) -> u16 {
+        self.weighted(|column| self.column_total(column))
+    }
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self::for_rules(&Ruleset::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Score;
+    use crate::{
+        hand::Hand,
+        rules::{Ruleset, Variant},
+        scoring::{JokerRule, ScoreType},
+    };
+
+    const LOWER: [ScoreType; 7] = [
+        ScoreType::ThreeKind,
+        ScoreType::FourKind,
+        ScoreType::TwoAndThree,
+        ScoreType::SmStraight,
+        ScoreType::LgStraight,
+        ScoreType::AllFive,
+        ScoreType::StoneSoup,
+    ];
+
+    /// A standard card with AllFive already filled
+    fn after_all_five(rules: &Ruleset, points: u16) -> Score {
+        let mut score = Score::for_rules(rules);
+        score.take(ScoreType::AllFive, points);
+        score
+    }
+
+    #[test]
+    fn forced_joker_takes_the_matching_upper_box_first() {
+        let rules = Ruleset::default();
+        let score = after_all_five(&rules, 50);
+        let fours = Hand::from_values(&[4; 5]).unwrap();
+        assert!(score.is_joker(0, &fours, &rules));
+        assert!(score.can_take(0, ScoreType::Fours, &fours, &rules));
+        assert!(!score.can_take(0, ScoreType::Ones, &fours, &rules));
+        assert!(!score.can_take(0, ScoreType::LgStraight, &fours, &rules));
+        assert_eq!(score.points_for(0, ScoreType::Fours, &fours, &rules), 20);
+    }
+
+    #[test]
+    fn forced_joker_falls_through_to_lower_boxes_at_full_value() {
+        let rules = Ruleset::default();
+        let mut score = after_all_five(&rules, 50);
+        score.take(ScoreType::Fours, 12);
+        let fours = Hand::from_values(&[4; 5]).unwrap();
+        assert!(!score.can_take(0, ScoreType::Ones, &fours, &rules));
+        let points = |score: &Score, category| score.points_for(0, category, &fours, &rules);
+        assert!(score.can_take(0, ScoreType::LgStraight, &fours, &rules));
+        assert_eq!(points(&score, ScoreType::LgStraight), 40);
+        assert_eq!(points(&score, ScoreType::SmStraight), 30);
+        assert_eq!(points(&score, ScoreType::TwoAndThree), 25);
+        assert_eq!(points(&score, ScoreType::FourKind), 20);
+        // With the lower section full, any upper box will do, for a scratch
+        for category in LOWER {
+            score.take(category, 0);
+        }
+        assert!(score.can_take(0, ScoreType::Ones, &fours, &rules));
+        assert_eq!(points(&score, ScoreType::Ones), 0);
+    }
+
+    #[test]
+    fn free_choice_joker_goes_anywhere_and_no_joker_scores_plainly() {
+        let free = Ruleset::default().with_joker_rule(JokerRule::FreeChoice);
+        let score = after_all_five(&free, 50);
+        let fours = Hand::from_values(&[4; 5]).unwrap();
+        assert!(score.can_take(0, ScoreType::Ones, &fours, &free));
+        assert!(score.can_take(0, ScoreType::LgStraight, &fours, &free));
+        assert_eq!(
+            score.points_for(0, ScoreType::LgStraight, &fours, &free),
+            40
+        );
+
+        let plain = Ruleset::default().with_joker_rule(JokerRule::NoJoker);
+        let score = after_all_five(&plain, 50);
+        assert!(!score.is_joker(0, &fours, &plain));
+        assert!(score.can_take(0, ScoreType::Ones, &fours, &plain));
+        assert_eq!(
+            score.points_for(0, ScoreType::LgStraight, &fours, &plain),
+            0
+        );
+        assert_eq!(score.points_for(0, ScoreType::FourKind, &fours, &plain), 20);
+    }
+
+    #[test]
+    fn all_five_bonus_needs_a_scored_all_five() {
+        let rules = Ruleset::default();
+        let fours = Hand::from_values(&[4; 5]).unwrap();
+        let bonus = |score: &Score| score.slot(ScoreType::AllFiveBonus).unwrap().points;
+        let mut score = after_all_five(&rules, 50);
+        score.award_all_five_bonus(0, &fours, &rules);
+        assert_eq!(bonus(&score), 100);
+        score.award_all_five_bonus(0, &fours, &rules);
+        assert_eq!(bonus(&score), 200);
+        // Only five of a kind earns it
+        score.award_all_five_bonus(0, &Hand::from_values(&[4, 4, 4, 4, 5]).unwrap(), &rules);
+        assert_eq!(bonus(&score), 200);
+
+        let mut scratched = after_all_five(&rules, 0);
+        scratched.award_all_five_bonus(0, &fours, &rules);
+        assert_eq!(bonus(&scratched), 0);
+        // The joker rule still applies after a scratch
+        assert!(scratched.is_joker(0, &fours, &rules));
+    }
+
+    #[test]
+    fn double_all_five_scores_only_after_all_five() {
+        let generala = Variant::Generala.rules();
+        let mut score = Score::for_rules(&generala);
+        let hand = Hand::from_values(&[3; 5]).unwrap();
+        assert_eq!(
+            score.points_for(0, ScoreType::DoubleAllFive, &hand, &generala),
+            0
+        );
+        score.take(ScoreType::AllFive, 50);
+        assert_eq!(
+            score.points_for(0, ScoreType::DoubleAllFive, &hand, &generala),
+            100
+        );
+    }
+
+    #[test]
+    fn required_category_has_to_score_points_first() {
+        let rules: Ruleset = "category Sixes face\ncategory StoneSoup chance sum requires Sixes"
+            .parse()
+            .unwrap();
+        let mut score = Score::for_rules(&rules);
+        let hand = Hand::from_values(&[1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(score.points_for(0, ScoreType::StoneSoup, &hand, &rules), 0);
+        // A scratch doesn't count
+        score.take(ScoreType::Sixes, 0);
+        assert_eq!(score.points_for(0, ScoreType::StoneSoup, &hand, &rules), 0);
+        let mut score = Score::for_rules(&rules);
+        score.take(ScoreType::Sixes, 12);
+        assert_eq!(score.points_for(0, ScoreType::StoneSoup, &hand, &rules), 15);
+    }
+}
@@ -0,0 +1,369 @@
+// scoring.rs turns a Hand into points for each category
+
+use crate::{
+    hand::Hand,
+    names::{by_name, Cycle, Named},
+};
+use serde::{Deserialize, Serialize};
+
+/// How a five of a kind may be placed once the AllFive box is already filled
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JokerRule {
+    /// Official rules - the matching upper box must be used first, then any lower box at full value
+    #[default]
+    Forced,
+    /// Any open box may take the roll, lower boxes at full value
+    FreeChoice,
+    /// No jokers - the roll scores by the normal rules wherever it goes
+    NoJoker,
+}
+
+impl Named for JokerRule {}
+
+impl Cycle for JokerRule {
+    const ORDER: &'static [Self] = &[JokerRule::Forced, JokerRule::FreeChoice, JokerRule::NoJoker];
+}
+
+/// Each possible option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScoreType {
+    Ones,
+    Twos,
+    Threes,
+    Fours,
+    Fives,
+    Sixes,
+    OnePair,
+    TwoPairs,
+    ThreePairs,
+    ThreeKind,
+    FourKind,
+    FiveKind,
+    TwoAndThree,
+    Castle,
+    Tower,
+    SmStraight,
+    LgStraight,
+    FullStraight,
+    AllFive,
+    DoubleAllFive,
+    MaxiYatzy,
+    AllFiveBonus,
+    StoneSoup,
+}
+
+impl ScoreType {
+    /// Every category of the standard rules, in scorecard order - the ones the solver knows
+    pub const ALL: [ScoreType; 13] = [
+        ScoreType::Ones,
+        ScoreType::Twos,
+        ScoreType::Threes,
+        ScoreType::Fours,
+        ScoreType::Fives,
+        ScoreType::Sixes,
+        ScoreType::ThreeKind,
+        ScoreType::FourKind,
+        ScoreType::TwoAndThree,
+        ScoreType::SmStraight,
+        ScoreType::LgStraight,
+        ScoreType::AllFive,
+        ScoreType::StoneSoup,
+    ];
+
+    /// Categories only other rulesets use, beside the standard ones
+    pub const EXTRA: [ScoreType; 9] = [
+        ScoreType::OnePair,
+        ScoreType::TwoPairs,
+        ScoreType::ThreePairs,
+        ScoreType::FiveKind,
+        ScoreType::Castle,
+        ScoreType::Tower,
+        ScoreType::FullStraight,
+        ScoreType::MaxiYatzy,
+        ScoreType::DoubleAllFive,
+    ];
+
+    /// The face value counted by an upper section category
+    pub fn face(self) -> Option<u8> {
+        use ScoreType::*;
+        match self {
+            Ones => Some(1),
+            Twos => Some(2),
+            Threes => Some(3),
+            Fours => Some(4),
+            Fives => Some(5),
+            Sixes => Some(6),
+            _ => None,
+        }
+    }
+
+    /// Whether this category belongs to the upper section
+    pub fn is_upper(self) -> bool {
+        self.face().is_some()
+    }
+
+    /// The upper section category counting the given face
+    pub fn from_face(face: u8) -> Option<Self> {
+        ScoreType::ALL
+            .iter()
+            .find(|category| category.face() == Some(face))
+            .copied()
+    }
+}
+
+impl Named for ScoreType {
+    /// Look up a choosable category by its name - the AllFive bonus is earned, never chosen
+    fn from_name(name: &str) -> Option<Self> {
+        by_name(name).filter(|category| *category != ScoreType::AllFiveBonus)
+    }
+}
+
+/// How the dice a category counts are turned into points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Points {
+    /// Every die in the hand
+    Sum,
+    /// Only the dice that fill the pattern, making the best of them
+    Matched,
+    /// A flat amount
+    Fixed(u16),
+}
+
+/// What a hand needs to fill a category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pattern {
+    /// Any hand - the dice showing the category's own face count
+    Face,
+    /// Any hand at all
+    Chance,
+    /// A different face for each group, showing at least that many times
+    Groups(Vec<u8>),
+    /// This many faces in a row
+    Run(u8),
+    /// Each of these faces showing
+    Faces(Vec<u8>),
+}
+
+/// How one category is filled and scored
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     hand::Hand,
+///     scoring::{CategoryRule, Pattern, Points, ScoreType},
+/// };
+///
+/// let hand = Hand::from_values(&[3, 3, 3, 5, 5]).unwrap();
+/// let rule = |category, pattern, points| CategoryRule { category, pattern, points, served: 0, requires: None };
+/// let threes = rule(ScoreType::Threes, Pattern::Face, Points::Matched);
+/// assert_eq!(threes.score(&hand), 9);
+/// let three_kind = rule(ScoreType::ThreeKind, Pattern::Groups(vec![3]), Points::Sum);
+/// assert_eq!(three_kind.score(&hand), 19);
+/// let four_kind = rule(ScoreType::FourKind, Pattern::Groups(vec![4]), Points::Sum);
+/// assert_eq!(four_kind.score(&hand), 0);
+/// let full_house = rule(ScoreType::TwoAndThree, Pattern::Groups(vec![3, 2]), Points::Fixed(25));
+/// assert_eq!(full_house.score(&hand), 25);
+///
+/// let straight = Hand::from_values(&[2, 3, 4, 5, 6]).unwrap();
+/// let small = rule(ScoreType::SmStraight, Pattern::Run(4), Points::Fixed(30));
+/// assert_eq!(small.score(&straight), 30);
+/// // Matched points take the best run on offer
+/// let small = rule(ScoreType::SmStraight, Pattern::Run(4), Points::Matched);
+/// assert_eq!(small.score(&straight), 18);
+/// let high = rule(ScoreType::LgStraight, Pattern::Faces(vec![2, 3, 4, 5, 6]), Points::Matched);
+/// assert_eq!(high.score(&straight), 20);
+/// assert_eq!(high.score(&hand), 0);
+/// let chance = rule(ScoreType::StoneSoup, Pattern::Chance, Points::Sum);
+/// assert_eq!(chance.score(&straight), 20);
+///
+/// // A bonus for filling the pattern on the turn's first roll
+/// let full_house = CategoryRule { served: 5, ..full_house };
+/// assert_eq!(full_house.score(&hand), 25);
+/// assert_eq!(full_house.score(&Hand::served(&[3, 3, 3, 5, 5]).unwrap()), 30);
+/// assert_eq!(full_house.score(&Hand::served(&[3, 3, 3, 5, 6]).unwrap()), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub category: ScoreType,
+    pub pattern: Pattern,
+    pub points: Points,
+    // Extra points when the pattern is filled on the turn's first roll
+    pub served: u16,
+    // A category that has to have scored points in the column before this one can
+    pub requires: Option<ScoreType>,
+}
+
+impl CategoryRule {
+    /// The points from the dice that fill the pattern, if the hand fills it at all
+    fn matched(&self, counts: &[u8; 6]) -> Option<u16> {
+        match &self.pattern {
+            Pattern::Face => {
+                let face = self.category.face()?;
+                Some(u16::from(counts[face as usize - 1] * face))
+            }
+            Pattern::Chance => Some(sum(counts)),
+            Pattern::Groups(sizes) => best_groups(counts, sizes, 0),
+            Pattern::Run(length) => {
+                // The highest run that fits
+                let length = *length as usize;
+                (length..=6).rev().find_map(|top| {
+                    let faces = top + 1 - length..=top;
+                    if faces.clone().all(|face| counts[face - 1] > 0) {
+                        Some(faces.map(|face| face as u16).sum())
+                    } else {
+                        None
+                    }
+                })
+            }
+            Pattern::Faces(faces) => {
+                let mut left = *counts;
+                for face in faces {
+                    let count = left.get_mut((*face as usize).wrapping_sub(1))?;
+                    *count = count.checked_sub(1)?;
+                }
+                Some(faces.iter().map(|face| u16::from(*face)).sum())
+            }
+        }
+    }
+
+    /// Whether the hand fills this category for more than a scratch
+    pub fn makes(&self, hand: &Hand) -> bool {
+        self.matched(&counts(hand)).is_some()
+    }
+
+    /// Return the points this hand is worth in this category - 0 if it doesn't qualify
+    /// Hands served on the first roll earn the category's served bonus on top
+    pub fn score(&self, hand: &Hand) -> u16 {
+        let counts = counts(hand);
+        let served = if hand.is_served() { self.served } else { 0 };
+        match (self.matched(&counts), self.points) {
+            (None, _) => 0,
+            (Some(_), Points::Sum) => sum(&counts).saturating_add(served),
+            (Some(matched), Points::Matched) => matched.saturating_add(served),
+            (Some(_), Points::Fixed(points)) => points.saturating_add(served),
+        }
+    }
+
+    /// Return the points this hand is worth when played as a joker
+    /// Lower section boxes score in full whether or not the hand qualifies
+    pub fn joker_score(&self, hand: &Hand) -> u16 {
+        match (&self.pattern, self.points) {
+            (Pattern::Face, _) => self.score(hand),
+            (_, Points::Fixed(points)) => points,
+            _ => sum(&counts(hand)),
+        }
+    }
+}
+
+/// Whether every die shows the same face
+pub fn is_all_five(hand: &Hand) -> bool {
+    let values = hand.values();
+    values.iter().all(|v| *v == values[0])
+}
+
+/// How many of each face are showing, indexed from One at 0
+fn counts(hand: &Hand) -> [u8; 6] {
+    let mut ret = [0; 6];
+    for value in hand.values().iter() {
+        ret[*value as usize - 1] += 1;
+    }
+    ret
+}
+
+/// Total of all the dice
+fn sum(counts: &[u8; 6]) -> u16 {
+    counts
+        .iter()
+        .zip(1..)
+        .map(|(count, face)| u16::from(*count) * face)
+        .sum()
+}
+
+/// The most the groups can count, each on a different face from `used` on, if they all fit
+fn best_groups(counts: &[u8; 6], sizes: &[u8], used: u8) -> Option<u16> {
+    let (size, rest) = match sizes.split_first() {
+        Some(split) => split,
+        None => return Some(0),
+    };
+    (0..6)
+        .filter(|face| used & 1 << face == 0 && counts[*face] >= *size)
+        .filter_map(|face| {
+            let points = u16::from(*size) * (face as u16 + 1);
+            best_groups(counts, rest, used | 1 << face).map(|more| points + more)
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoreType;
+    use crate::{hand::Hand, names::Named, rules::Variant};
+
+    fn hand(values: &[u8]) -> Hand {
+        Hand::from_values(values).unwrap()
+    }
+
+    #[test]
+    fn every_choosable_category_is_found_by_name() {
+        for category in ScoreType::ALL.iter().chain(ScoreType::EXTRA.iter()) {
+            let name = format!("{:?}", category);
+            assert_eq!(ScoreType::from_name(&name), Some(*category));
+        }
+        assert_eq!(ScoreType::from_name("AllFiveBonus"), None);
+        assert_eq!(ScoreType::from_name("Sevens"), None);
+    }
+
+    #[test]
+    fn yatzy_scores_the_dice_that_fill_each_category() {
+        let yatzy = Variant::Yatzy.rules();
+        assert_eq!(yatzy.upper_bonus(), &[(63, 50)]);
+        let full_house = hand(&[4, 4, 6, 6, 6]);
+        assert_eq!(yatzy.points(ScoreType::OnePair, &full_house), 12);
+        assert_eq!(yatzy.points(ScoreType::TwoPairs, &full_house), 20);
+        assert_eq!(yatzy.points(ScoreType::ThreeKind, &full_house), 18);
+        assert_eq!(yatzy.points(ScoreType::TwoAndThree, &full_house), 26);
+        // A four of a kind isn't two pairs, and five alike isn't a full house
+        let four_kind = hand(&[5, 5, 5, 5, 2]);
+        assert_eq!(yatzy.points(ScoreType::TwoPairs, &four_kind), 0);
+        assert_eq!(yatzy.points(ScoreType::FourKind, &four_kind), 20);
+        let all_five = hand(&[3; 5]);
+        assert_eq!(yatzy.points(ScoreType::TwoAndThree, &all_five), 0);
+        assert_eq!(yatzy.points(ScoreType::AllFive, &all_five), 50);
+        // Straights are fixed runs of faces
+        let small = hand(&[5, 1, 4, 2, 3]);
+        assert_eq!(yatzy.points(ScoreType::SmStraight, &small), 15);
+        assert_eq!(yatzy.points(ScoreType::LgStraight, &small), 0);
+    }
+
+    #[test]
+    fn maxi_yatzy_scores_six_dice() {
+        let maxi = Variant::MaxiYatzy.rules();
+        let castle = hand(&[2, 2, 2, 5, 5, 5]);
+        assert_eq!(maxi.points(ScoreType::Castle, &castle), 21);
+        assert_eq!(maxi.points(ScoreType::ThreePairs, &castle), 0);
+        assert_eq!(maxi.points(ScoreType::Tower, &castle), 0);
+        // A full house leaves out the sixth die
+        let full_house = hand(&[2, 2, 2, 5, 5, 6]);
+        assert_eq!(maxi.points(ScoreType::TwoAndThree, &full_house), 16);
+        let straight = hand(&[6, 1, 4, 2, 3, 5]);
+        assert_eq!(maxi.points(ScoreType::FullStraight, &straight), 21);
+        let all_six = hand(&[4; 6]);
+        assert_eq!(maxi.points(ScoreType::MaxiYatzy, &all_six), 100);
+        assert_eq!(maxi.points(ScoreType::FiveKind, &all_six), 20);
+    }
+
+    #[test]
+    fn generala_pays_extra_for_served_hands() {
+        let generala = Variant::Generala.rules();
+        let served = Hand::served(&[2, 2, 5, 5, 5]).unwrap();
+        assert_eq!(generala.points(ScoreType::TwoAndThree, &served), 35);
+        assert_eq!(
+            generala.points(ScoreType::TwoAndThree, &hand(&[2, 2, 5, 5, 5])),
+            30
+        );
+        // Only a made category earns the bonus
+        assert_eq!(generala.points(ScoreType::FourKind, &served), 0);
+        let straight = Hand::served(&[3, 4, 5, 6, 2]).unwrap();
+        assert_eq!(generala.points(ScoreType::LgStraight, &straight), 25);
+    }
+}
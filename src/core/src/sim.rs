@@ -0,0 +1,198 @@
+// sim.rs plays whole games with no one watching and sums up how they went
+
+use crate::{
+    controller::Controller,
+    dice::SeededDice,
+    game::{Game, GamePhase},
+    rules::Ruleset,
+    score::Score,
+    scoring::ScoreType,
+};
+
+/// Play solitaire games back to back with one controller
+/// Game `n` rolls dice seeded with `seed + n`, so any single game can be played again
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{controller::GreedyController, rules::Ruleset, sim::simulate};
+///
+/// let rules = Ruleset::default();
+/// let summary = simulate(20, &rules, 1, &mut GreedyController);
+/// assert_eq!(summary.games(), 20);
+/// assert!(summary.percentile(0.0) <= summary.percentile(50.0));
+/// assert!(summary.percentile(50.0) <= summary.percentile(100.0));
+/// assert!(summary.mean() > 100.0 && summary.stddev() > 0.0);
+/// let counted: usize = summary.histogram(25).iter().map(|(_, n)| n).sum();
+/// assert_eq!(counted, 20);
+///
+/// // The same seed plays the same games
+/// assert_eq!(simulate(20, &rules, 1, &mut GreedyController), summary);
+/// ```
+pub fn simulate(
+    games: usize,
+    rules: &Ruleset,
+    seed: u64,
+    controller: &mut dyn Controller,
+) -> Summary {
+    let mut summary = Summary::new(rules);
+    for n in 0..games {
+        let dice = Box::new(SeededDice::new(seed.wrapping_add(n as u64)));
+        let mut game = Game::with_rules(&["Sim"], rules.clone(), dice);
+        while game.phase() == GamePhase::Playing {
+            let msg = controller.next_move(&game);
+            game.reducer(msg)
+                .expect("controllers only send moves the rules allow");
+        }
+        summary.add(game.players()[0].score());
+    }
+    summary
+}
+
+/// Totals and rates from a batch of finished scorecards
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    // Every final total, in the order played
+    totals: Vec<u16>,
+    upper_bonuses: usize,
+    // Games with AllFive scored, and games with at least one extra AllFive bonus
+    all_fives: usize,
+    all_five_bonuses: usize,
+    // Points in each category summed over every column and game, in scorecard order
+    points: Vec<(ScoreType, u64)>,
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Self::new(&Ruleset::default())
+    }
+}
+
+impl Summary {
+    /// An empty summary with a slot for every category of these rules
+    pub fn new(rules: &Ruleset) -> Self {
+        Self {
+            totals: Vec::new(),
+            upper_bonuses: 0,
+            all_fives: 0,
+            all_five_bonuses: 0,
+            points: Score::for_rules(rules)
+                .column_slots(0)
+                .map(|slot| (slot.category(), 0))
+                .collect(),
+        }
+    }
+
+    /// Add a finished scorecard
+    pub fn add(&mut self, score: &Score) {
+        self.totals.push(score.total());
+        if score.upper_bonus() > 0 {
+            self.upper_bonuses += 1;
+        }
+        let scored = |category| {
+            score
+                .slots()
+                .iter()
+                .any(|slot| slot.category() == category && slot.points() > 0)
+        };
+        if scored(ScoreType::AllFive) {
+            self.all_fives += 1;
+        }
+        if scored(ScoreType::AllFiveBonus) {
+            self.all_five_bonuses += 1;
+        }
+        for slot in score.slots() {
+            if let Some((_, points)) = self.points.iter_mut().find(|(c, _)| *c == slot.category()) {
+                *points += u64::from(slot.points());
+            }
+        }
+    }
+
+    /// Games added so far
+    pub fn games(&self) -> usize {
+        self.totals.len()
+    }
+
+    /// How much of the time something happened, as a fraction of games played
+    fn rate(&self, count: usize) -> f64 {
+        if self.totals.is_empty() {
+            0.0
+        } else {
+            count as f64 / self.totals.len() as f64
+        }
+    }
+
+    /// Mean final total
+    pub fn mean(&self) -> f64 {
+        if self.totals.is_empty() {
+            0.0
+        } else {
+            let sum: u64 = self.totals.iter().map(|t| u64::from(*t)).sum();
+            sum as f64 / self.totals.len() as f64
+        }
+    }
+
+    /// Standard deviation of the final totals
+    pub fn stddev(&self) -> f64 {
+        let mean = self.mean();
+        let squares: f64 = self
+            .totals
+            .iter()
+            .map(|t| (f64::from(*t) - mean).powi(2))
+            .sum();
+        if self.totals.is_empty() {
+            0.0
+        } else {
+            (squares / self.totals.len() as f64).sqrt()
+        }
+    }
+
+    /// The final total below which `pct` percent of games fall, by nearest rank
+    pub fn percentile(&self, pct: f64) -> u16 {
+        let mut sorted = self.totals.clone();
+        sorted.sort_unstable();
+        if sorted.is_empty() {
+            return 0;
+        }
+        let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    /// Games in each bucket of `width` points, from the lowest bucket reached to the highest
+    /// Each bucket is labelled with the lowest total it holds
+    pub fn histogram(&self, width: u16) -> Vec<(u16, usize)> {
+        let width = width.max(1);
+        let (low, high) = match (self.totals.iter().min(), self.totals.iter().max()) {
+            (Some(low), Some(high)) => (low / width, high / width),
+            _ => return Vec::new(),
+        };
+        (low..=high)
+            .map(|bucket| {
+                let count = self.totals.iter().filter(|t| **t / width == bucket).count();
+                (bucket * width, count)
+            })
+            .collect()
+    }
+
+    /// Fraction of games that earned the upper bonus
+    pub fn upper_bonus_rate(&self) -> f64 {
+        self.rate(self.upper_bonuses)
+    }
+
+    /// Fraction of games with AllFive scored
+    pub fn all_five_rate(&self) -> f64 {
+        self.rate(self.all_fives)
+    }
+
+    /// Fraction of games with at least one extra AllFive bonus
+    pub fn all_five_bonus_rate(&self) -> f64 {
+        self.rate(self.all_five_bonuses)
+    }
+
+    /// Mean points in each slot, in scorecard order
+    pub fn category_averages(&self) -> Vec<(ScoreType, f64)> {
+        self.points
+            .iter()
+            .map(|(category, points)| (*category, *points as f64 / self.games().max(1) as f64))
+            .collect()
+    }
+}
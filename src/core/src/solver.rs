@@ -0,0 +1,600 @@
+// solver.rs works out the expected-value-optimal play for a single player
+
+use crate::{
+    error::{GameError, Result},
+    hand::{Hand, ROLLS_PER_TURN, STANDARD_DICE},
+    rules::Ruleset,
+    score::Score,
+    scoring::{JokerRule, ScoreType},
+};
+use std::collections::HashMap;
+
+/// Version written into every exported table - bump it whenever the format changes
+pub const TABLE_VERSION: u8 = 1;
+
+// First bytes of every exported table, so other files are turned away early
+const TABLE_MAGIC: &[u8; 4] = b"FDEV";
+
+// The standard rules' bonuses - the only ones the tables are solved for
+const ALL_FIVE_POINTS: u16 = 50;
+const ALL_FIVE_BONUS_POINTS: u16 = 100;
+const UPPER_BONUS_THRESHOLD: u16 = 63;
+const UPPER_BONUS_POINTS: u16 = 35;
+
+// Categories tracked in a state's open set, in ScoreType::ALL order
+const CATEGORIES: usize = ScoreType::ALL.len();
+
+// Upper progress past the threshold is all the same to the bonus
+const UPPER_CAP: usize = UPPER_BONUS_THRESHOLD as usize;
+
+// Every combination of open set, upper progress and AllFive bonus eligibility
+const STATES: usize = (1 << CATEGORIES) * (UPPER_CAP + 1) * 2;
+
+// Faces on a die
+const FACES: usize = 6;
+
+/// The parts of a scorecard that matter for the rest of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    // Bit per category still open, in ScoreType::ALL order
+    open: usize,
+    // Upper section subtotal, capped at the bonus threshold
+    upper: usize,
+    // Whether AllFive was scored for full points, so another earns the bonus
+    bonus: bool,
+}
+
+impl State {
+    fn from_score(score: &Score) -> Self {
+        let open = ScoreType::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, category)| score.is_open(**category))
+            .fold(0, |open, (idx, _)| open | 1 << idx);
+        let bonus = score
+            .slot(ScoreType::AllFive)
+            .is_some_and(|slot| slot.is_taken() && slot.points() == ALL_FIVE_POINTS);
+        Self {
+            open,
+            upper: (score.upper_subtotal() as usize).min(UPPER_CAP),
+            bonus,
+        }
+    }
+
+    /// Position in the full value table
+    fn index(self) -> usize {
+        (self.open * (UPPER_CAP + 1) + self.upper) * 2 + self.bonus as usize
+    }
+
+    fn is_open(self, category: usize) -> bool {
+        self.open & 1 << category != 0
+    }
+}
+
+/// Position of a category in ScoreType::ALL
+fn category_index(category: ScoreType) -> usize {
+    ScoreType::ALL.iter().position(|c| *c == category).unwrap()
+}
+
+/// Every state that can follow from `start`, in an order where each comes after all it leads to
+fn reachable_states(start: State) -> Vec<State> {
+    let all_five = category_index(ScoreType::AllFive);
+    let mut ret = Vec::new();
+    for open in 0..=start.open {
+        // Only categories open at the start can be open later
+        if open & !start.open != 0 {
+            continue;
+        }
+        // Upper progress can only come from the upper categories filled since the start
+        let mut uppers: u64 = 1 << start.upper;
+        for (idx, category) in ScoreType::ALL.iter().enumerate() {
+            let face = match category.face() {
+                Some(face) if start.is_open(idx) && open & 1 << idx == 0 => face as usize,
+                _ => continue,
+            };
+            let mut next = 0;
+            for upper in (0..=UPPER_CAP).filter(|u| uppers & 1 << u != 0) {
+                for count in 0..=STANDARD_DICE {
+                    next |= 1 << (upper + count * face).min(UPPER_CAP);
+                }
+            }
+            uppers = next;
+        }
+        // The bonus can only be earned while AllFive is being filled
+        let bonuses: &[bool] = if open & 1 << all_five != 0 {
+            &[false]
+        } else if start.is_open(all_five) {
+            &[false, true]
+        } else if start.bonus {
+            &[true]
+        } else {
+            &[false]
+        };
+        for upper in (0..=UPPER_CAP).filter(|u| uppers & 1 << u != 0) {
+            for bonus in bonuses {
+                ret.push(State {
+                    open,
+                    upper,
+                    bonus: *bonus,
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// Every multiset of up to a hand's worth of dice, and how they fit together
+#[derive(Debug, Clone)]
+pub(crate) struct Dice {
+    // Face counts, largest multisets first so the full hands come before anything held
+    pub(crate) counts: Vec<[u8; FACES]>,
+    // The multiset after adding one more die of each face, for anything short of a hand
+    add: Vec<[usize; FACES]>,
+    // How many of the multisets are full hands
+    pub(crate) hands: usize,
+    // Every distinct way of holding dice from each full hand
+    holds: Vec<Vec<usize>>,
+    pub(crate) index: HashMap<[u8; FACES], usize>,
+}
+
+impl Dice {
+    /// The tables for hands of `size` dice
+    pub(crate) fn new(size: usize) -> Self {
+        // All face counts adding up to size or less
+        let mut counts = vec![[0; FACES]];
+        for face in 0..FACES {
+            let mut next = Vec::new();
+            for c in &counts {
+                let held: u8 = c.iter().sum();
+                for n in 0..=(size as u8 - held) {
+                    let mut c = *c;
+                    c[face] = n;
+                    next.push(c);
+                }
+            }
+            counts = next;
+        }
+        counts.sort_by_key(|c| std::cmp::Reverse(c.iter().sum::<u8>()));
+        let index: HashMap<[u8; FACES], usize> =
+            counts.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+        let hands = counts
+            .iter()
+            .filter(|c| c.iter().sum::<u8>() as usize == size)
+            .count();
+        let add = counts
+            .iter()
+            .map(|c| {
+                let mut ret = [usize::MAX; FACES];
+                if (c.iter().sum::<u8>() as usize) < size {
+                    for (face, slot) in ret.iter_mut().enumerate() {
+                        let mut c = *c;
+                        c[face] += 1;
+                        *slot = index[&c];
+                    }
+                }
+                ret
+            })
+            .collect();
+        let mut holds = Vec::new();
+        for c in &counts[..hands] {
+            // Sub-multisets, one face at a time
+            let mut subs = vec![[0; FACES]];
+            for face in 0..FACES {
+                subs = subs
+                    .iter()
+                    .flat_map(|s| {
+                        (0..=c[face]).map(move |n| {
+                            let mut s = *s;
+                            s[face] = n;
+                            s
+                        })
+                    })
+                    .collect();
+            }
+            holds.push(subs.iter().map(|s| index[s]).collect());
+        }
+        Self {
+            counts,
+            add,
+            hands,
+            holds,
+            index,
+        }
+    }
+
+    /// A hand showing these face counts
+    pub(crate) fn hand(counts: &[u8; FACES]) -> Hand {
+        let values: Vec<u8> = counts
+            .iter()
+            .enumerate()
+            .flat_map(|(face, n)| std::iter::repeat_n(face as u8 + 1, *n as usize))
+            .collect();
+        Hand::from_values(&values).unwrap()
+    }
+
+    /// Face counts of the dice showing in a hand
+    pub(crate) fn counts_of(values: &[u8]) -> [u8; FACES] {
+        let mut ret = [0; FACES];
+        for value in values {
+            ret[*value as usize - 1] += 1;
+        }
+        ret
+    }
+
+    /// Expected value of holding each multiset and rolling the rest, given each full hand's value
+    pub(crate) fn keep_values(&self, hand_values: &[f64]) -> Vec<f64> {
+        let mut ret = vec![0.0; self.counts.len()];
+        ret[..self.hands].copy_from_slice(hand_values);
+        // Rolling the missing dice one at a time lands the same way as rolling them together
+        for k in self.hands..self.counts.len() {
+            ret[k] = self.add[k].iter().map(|next| ret[*next]).sum::<f64>() / FACES as f64;
+        }
+        ret
+    }
+
+    /// Value of each full hand with a roll left, holding the best dice from it
+    pub(crate) fn hold_values(&self, keep_values: &[f64]) -> Vec<f64> {
+        self.holds
+            .iter()
+            .map(|holds| {
+                holds
+                    .iter()
+                    .map(|k| keep_values[*k])
+                    .fold(f64::MIN, f64::max)
+            })
+            .collect()
+    }
+}
+
+/// What each full standard hand scores, by its place in the dice tables
+#[derive(Debug, Clone)]
+struct HandPoints {
+    // Points in each category, normally and as a joker
+    points: Vec<[u16; CATEGORIES]>,
+    joker_points: Vec<[u16; CATEGORIES]>,
+    // The face showing on every die of a five of a kind
+    all_five: Vec<Option<u8>>,
+}
+
+impl HandPoints {
+    fn new(dice: &Dice) -> Self {
+        // The tables only know the standard categories
+        let rules = Ruleset::default();
+        let mut ret = Self {
+            points: Vec::new(),
+            joker_points: Vec::new(),
+            all_five: Vec::new(),
+        };
+        for c in &dice.counts[..dice.hands] {
+            let hand = Dice::hand(c);
+            let mut p = [0; CATEGORIES];
+            let mut j = [0; CATEGORIES];
+            for (idx, category) in ScoreType::ALL.iter().enumerate() {
+                p[idx] = rules.points(*category, &hand);
+                j[idx] = rules.joker_points(*category, &hand);
+            }
+            ret.points.push(p);
+            ret.joker_points.push(j);
+            ret.all_five.push(
+                c.iter()
+                    .position(|n| *n as usize == STANDARD_DICE)
+                    .map(|face| face as u8 + 1),
+            );
+        }
+        ret
+    }
+}
+
+/// What to hold before the next roll
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoldAdvice {
+    /// Which dice to hold, by position in the hand
+    /// Holding every die means scoring now is as good as anything
+    pub held: Vec<bool>,
+    /// The final total this leads to on average
+    pub expected: f64,
+}
+
+/// Where to score the hand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreAdvice {
+    pub category: ScoreType,
+    /// The final total this leads to on average
+    pub expected: f64,
+}
+
+/// The expected-value-optimal solitaire strategy under one joker rule
+///
+/// Holds the expected points still to come from every reachable scorecard,
+/// so the best hold or category for any hand is a quick lookup away
+///
+/// # Examples
+/// ```
+/// use fivedice_core::{
+///     hand::Hand,
+///     score::Score,
+///     scoring::{JokerRule, ScoreType},
+///     solver::Strategy,
+/// };
+///
+/// // A card with only the given category left open
+/// fn only(category: ScoreType) -> Score {
+///     let mut score = Score::new();
+///     for c in ScoreType::ALL.iter().filter(|c| **c != category) {
+///         score.take(*c, 0);
+///     }
+///     score
+/// }
+///
+/// // StoneSoup holds the fours and up - 23.33 on average
+/// let score = only(ScoreType::StoneSoup);
+/// let strategy = Strategy::solve_from(&score, JokerRule::Forced);
+/// assert!((strategy.expected_total(&score) - 23.33).abs() < 0.01);
+///
+/// // Chasing an AllFive over three rolls lands 4.6% of the time
+/// let score = only(ScoreType::AllFive);
+/// let strategy = Strategy::solve_from(&score, JokerRule::Forced);
+/// assert!((strategy.expected_total(&score) - 50.0 * 0.046029).abs() < 0.001);
+///
+/// // Chasing Sixes holds every six and nothing else
+/// let score = only(ScoreType::Sixes);
+/// let strategy = Strategy::solve_from(&score, JokerRule::Forced);
+/// let expected = 6.0 * 5.0 * (1.0 - (5.0f64 / 6.0).powi(3));
+/// assert!((strategy.expected_total(&score) - expected).abs() < 0.001);
+/// let mut hand = Hand::from_values(&[6, 2, 6, 3, 5]).unwrap();
+/// hand.remaining_rolls = 2;
+/// let advice = strategy.advise_hold(&score, &hand).unwrap();
+/// assert_eq!(advice.held, [true, false, true, false, false]);
+/// assert_eq!(
+///     strategy.advise_score(&score, &hand).unwrap().category,
+///     ScoreType::Sixes
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Strategy {
+    rule: JokerRule,
+    dice: Dice,
+    points: HandPoints,
+    // Expected points still to come from each state, by State::index
+    values: Vec<f32>,
+}
+
+impl Strategy {
+    /// Solve every state of a full game - takes a while, best run natively in release
+    pub fn solve(rule: JokerRule) -> Self {
+        Self::solve_from(&Score::new(), rule)
+    }
+
+    /// Solve only the states that can follow from this scorecard
+    pub fn solve_from(score: &Score, rule: JokerRule) -> Self {
+        let dice = Dice::new(STANDARD_DICE);
+        let mut ret = Self {
+            rule,
+            points: HandPoints::new(&dice),
+            dice,
+            values: vec![0.0; STATES],
+        };
+        for state in reachable_states(State::from_score(score)) {
+            if state.open != 0 {
+                ret.values[state.index()] = ret.turn_value(state) as f32;
+            }
+        }
+        ret
+    }
+
+    /// The joker rule this strategy plays by
+    pub fn joker_rule(&self) -> JokerRule {
+        self.rule
+    }
+
+    /// Whether these tables were solved for the given rules - the standard ones with this joker rule
+    pub fn fits(&self, rules: &Ruleset) -> bool {
+        *rules == Ruleset::default().with_joker_rule(self.rule)
+    }
+
+    /// The final total a scorecard leads to on average, starting a fresh turn
+    pub fn expected_total(&self, score: &Score) -> f64 {
+        f64::from(score.total()) + f64::from(self.values[State::from_score(score).index()])
+    }
+
+    /// The best hold for a rolled hand with rolls left, if there is one
+    pub fn advise_hold(&self, score: &Score, hand: &Hand) -> Option<HoldAdvice> {
+        if !hand.has_rolled() || hand.remaining_rolls == 0 {
+            return None;
+        }
+        let state = State::from_score(score);
+        if state.open == 0 {
+            return None;
+        }
+        let keeps = self.keep_values(state, hand.remaining_rolls - 1);
+        let values = hand.values();
+        // Only standard hands are in the tables
+        let h = *self.dice.index.get(&Dice::counts_of(&values))?;
+        let best = *self.dice.holds[h]
+            .iter()
+            .max_by(|a, b| keeps[**a].partial_cmp(&keeps[**b]).unwrap())?;
+        // Hold the first dice showing each kept face
+        let mut wanted = self.dice.counts[best];
+        let mut held = vec![false; values.len()];
+        for (held, value) in held.iter_mut().zip(values.iter()) {
+            let want = &mut wanted[*value as usize - 1];
+            if *want > 0 {
+                *want -= 1;
+                *held = true;
+            }
+        }
+        Some(HoldAdvice {
+            held,
+            expected: f64::from(score.total()) + keeps[best],
+        })
+    }
+
+    /// The best category for a rolled hand, if any are open
+    pub fn advise_score(&self, score: &Score, hand: &Hand) -> Option<ScoreAdvice> {
+        if !hand.has_rolled() {
+            return None;
+        }
+        let state = State::from_score(score);
+        let h = *self.dice.index.get(&Dice::counts_of(&hand.values()))?;
+        let (category, value) = self.best_category(state, h)?;
+        Some(ScoreAdvice {
+            category: ScoreType::ALL[category],
+            expected: f64::from(score.total()) + value,
+        })
+    }
+
+    /// The final total holding the dice already held leads to on average, if there's a roll left
+    pub fn expected_hold(&self, score: &Score, hand: &Hand) -> Option<f64> {
+        if !hand.has_rolled() || hand.remaining_rolls == 0 {
+            return None;
+        }
+        let state = State::from_score(score);
+        if state.open == 0 {
+            return None;
+        }
+        let keeps = self.keep_values(state, hand.remaining_rolls - 1);
+        let held: Vec<u8> = hand
+            .dice
+            .iter()
+            .filter(|die| die.is_held())
+            .map(|die| die.value() as u8)
+            .collect();
+        Some(f64::from(score.total()) + keeps[*self.dice.index.get(&Dice::counts_of(&held))?])
+    }
+
+    /// The final total scoring a rolled hand in this category leads to on average,
+    /// if the category can take it
+    pub fn expected_score(&self, score: &Score, hand: &Hand, category: ScoreType) -> Option<f64> {
+        if !hand.has_rolled() {
+            return None;
+        }
+        let c = ScoreType::ALL.iter().position(|c| *c == category)?;
+        let h = *self.dice.index.get(&Dice::counts_of(&hand.values()))?;
+        self.category_values(State::from_score(score), h)
+            .find(|(idx, _)| *idx == c)
+            .map(|(_, value)| f64::from(score.total()) + value)
+    }
+
+    /// Expected points from a fresh turn onwards
+    fn turn_value(&self, state: State) -> f64 {
+        let keeps = self.keep_values(state, ROLLS_PER_TURN - 1);
+        // Nothing held on the first roll
+        keeps[self.dice.counts.len() - 1]
+    }
+
+    /// Value of holding each multiset with this many rolls left after the next one
+    fn keep_values(&self, state: State, rolls_after: u8) -> Vec<f64> {
+        let mut hands: Vec<f64> = (0..self.dice.hands)
+            .map(|h| self.best_category(state, h).map_or(0.0, |(_, v)| v))
+            .collect();
+        for _ in 0..rolls_after {
+            hands = self.dice.hold_values(&self.dice.keep_values(&hands));
+        }
+        self.dice.keep_values(&hands)
+    }
+
+    /// The best category for a full hand and the points it leads to, this turn's included
+    fn best_category(&self, state: State, h: usize) -> Option<(usize, f64)> {
+        self.category_values(state, h)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+    }
+
+    /// Every category a full hand may go in, with the points each leads to, this turn's included
+    fn category_values(&self, state: State, h: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let all_five = self.points.all_five[h];
+        let all_five_idx = category_index(ScoreType::AllFive);
+        let joker =
+            self.rule != JokerRule::NoJoker && all_five.is_some() && !state.is_open(all_five_idx);
+        let bonus = if state.bonus && all_five.is_some() {
+            f64::from(ALL_FIVE_BONUS_POINTS)
+        } else {
+            0.0
+        };
+        // Forced jokers must go in the matching upper box first, then any lower box
+        let allowed = if joker && self.rule == JokerRule::Forced {
+            let upper = category_index(ScoreType::from_face(all_five.unwrap()).unwrap());
+            let lower = (0..CATEGORIES)
+                .filter(|c| !ScoreType::ALL[*c].is_upper())
+                .fold(0, |mask, c| mask | 1 << c);
+            if state.is_open(upper) {
+                1 << upper
+            } else if state.open & lower != 0 {
+                state.open & lower
+            } else {
+                state.open
+            }
+        } else {
+            state.open
+        };
+        let points = if joker {
+            &self.points.joker_points[h]
+        } else {
+            &self.points.points[h]
+        };
+        (0..CATEGORIES)
+            .filter(move |c| allowed & 1 << c != 0)
+            .map(move |c| {
+                let p = points[c] as usize;
+                let mut next = State {
+                    open: state.open & !(1 << c),
+                    ..state
+                };
+                let mut value = p as f64 + bonus;
+                if ScoreType::ALL[c].is_upper() {
+                    next.upper = (state.upper + p).min(UPPER_CAP);
+                    if state.upper < UPPER_CAP && next.upper == UPPER_CAP {
+                        value += f64::from(UPPER_BONUS_POINTS);
+                    }
+                }
+                if c == all_five_idx && p == ALL_FIVE_POINTS as usize {
+                    next.bonus = true;
+                }
+                (c, value + f64::from(self.values[next.index()]))
+            })
+    }
+
+    /// The full-game tables in a compact binary form - only reachable states are written
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let states = reachable_states(State::from_score(&Score::new()));
+        let mut ret = Vec::with_capacity(TABLE_MAGIC.len() + 2 + states.len() * 4);
+        ret.extend_from_slice(TABLE_MAGIC);
+        ret.push(TABLE_VERSION);
+        ret.push(self.rule as u8);
+        for state in states {
+            ret.extend_from_slice(&self.values[state.index()].to_le_bytes());
+        }
+        ret
+    }
+
+    /// Load tables written by to_bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let header = TABLE_MAGIC.len() + 2;
+        if bytes.len() < header || &bytes[..TABLE_MAGIC.len()] != TABLE_MAGIC {
+            return Err(GameError::BadTable);
+        }
+        if bytes[TABLE_MAGIC.len()] != TABLE_VERSION {
+            return Err(GameError::BadTable);
+        }
+        let rule = [JokerRule::Forced, JokerRule::FreeChoice, JokerRule::NoJoker]
+            .iter()
+            .find(|rule| **rule as u8 == bytes[TABLE_MAGIC.len() + 1])
+            .copied()
+            .ok_or(GameError::BadTable)?;
+        let states = reachable_states(State::from_score(&Score::new()));
+        let body = &bytes[header..];
+        if body.len() != states.len() * 4 {
+            return Err(GameError::BadTable);
+        }
+        let mut values = vec![0.0; STATES];
+        for (state, chunk) in states.iter().zip(body.chunks_exact(4)) {
+            values[state.index()] = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        let dice = Dice::new(STANDARD_DICE);
+        Ok(Self {
+            rule,
+            points: HandPoints::new(&dice),
+            dice,
+            values,
+        })
+    }
+}
@@ -0,0 +1,28 @@
+// dice.rs contains the browser sources of randomness for rolling and seeding the dice
+
+use fivedice_core::dice::{DiceSource, RollResult};
+use js_sys::Math::{floor, random};
+
+/// use js Math.random() to get an integer in range [min, max)
+pub fn js_gen_range(min: i64, max: i64) -> i64 {
+    (floor(random() * (max as f64 - min as f64)) + min as f64) as i64
+}
+
+/// Rolls using the browser's Math.random() - only usable in a WASM build
+/// Its rolls can't be replayed, so the page plays recorded games on SeededDice from js_seed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsDice;
+
+impl DiceSource for JsDice {
+    fn roll(&mut self) -> RollResult {
+        RollResult::from_face(js_gen_range(1, 7) as u8).unwrap()
+    }
+}
+
+/// A fresh seed for a recorded game, using the browser's Math.random()
+/// Built from two 32-bit halves, as an f64 can't carry all 64 bits
+pub fn js_seed() -> u64 {
+    let high = js_gen_range(0, 1 << 32) as u64;
+    let low = js_gen_range(0, 1 << 32) as u64;
+    (high << 32) | low
+}
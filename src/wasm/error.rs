@@ -1,4 +1,5 @@
 // error.rs contains the error type for the application
+use fivedice_core::error::GameError;
 use std::fmt;
 use wasm_bindgen::JsValue;
 use widget_grid::error::WindowError;
@@ -6,12 +7,14 @@ use widget_grid::error::WindowError;
 /// All possible Error types
 #[derive(Debug)]
 pub enum FiveDiceError {
+    Game(GameError),
     Window(WindowError),
 }
 
 impl fmt::Display for FiveDiceError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            Self::Game(e) => write!(f, "{}", e),
             Self::Window(e) => write!(f, "{}", e),
         }
     }
@@ -22,9 +25,15 @@ impl std::error::Error for FiveDiceError {}
 // Unused!
 //pub type Result<T> = std::result::Result<T, FiveDiceError>;
 
-impl Into<JsValue> for FiveDiceError {
-    fn into(self) -> JsValue {
-        format!("{}", self).into()
+impl From<FiveDiceError> for JsValue {
+    fn from(e: FiveDiceError) -> Self {
+        format!("{}", e).into()
+    }
+}
+
+impl From<GameError> for FiveDiceError {
+    fn from(e: GameError) -> Self {
+        FiveDiceError::Game(e)
     }
 }
 
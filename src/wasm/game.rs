@@ -1,232 +1,427 @@
-// game.rs contains the game logic
-
-use js_sys::Math::{floor, random};
-use std::{collections::HashSet, rc::Rc, str::FromStr};
-//use web_sys::console;
+// game.rs renders the game and feeds clicks back into its reducer
+
+use crate::{
+    dice::js_seed,
+    error::FiveDiceError,
+    records::{percent, today, RecordsScreen},
+    review::ReviewScreen,
+    storage::LocalStorage,
+    strategy::strategy,
+};
+use fivedice_core::{
+    controller::ControllerKind,
+    dice::Die,
+    game::{FiveDiceMessage, GamePhase, MAX_PLAYERS},
+    hand::Hand,
+    log::Recorder,
+    names::Cycle,
+    odds::Odds,
+    records::Records,
+    review::Review,
+    rules::{Ruleset, Variant},
+    save::{load, save},
+    score::{Score, ScoreSlot},
+    scoring::ScoreType,
+};
+use js_sys::Date;
+use std::{rc::Rc, str::FromStr};
+use web_sys::console;
 use widget_grid::{
-    traits::{MountedWidget, Widget},
-    types::{Callback, Color, Point},
+    traits::{Drawable, MountedWidget, Widget},
+    types::{Callback, Color, Font, Point, Region},
     widgets::{Button, Text},
     window::WindowPtr,
 };
 
 type WindowResult<T> = widget_grid::error::Result<T>;
 
-/// use js Math.random() to get an integer in range [min, max)
-pub fn js_gen_range(min: i64, max: i64) -> i64 {
-    (floor(random() * (max as f64 - min as f64)) + min as f64) as i64
-}
+// Canvas size, and the space the layout leaves around every widget
+pub const CANVAS_WIDTH: f64 = 1400.0;
+pub const CANVAS_HEIGHT: f64 = 1100.0;
+pub const PADDING: f64 = 10.0;
+
+// Widest a score slot can be - cells narrow from there as players and columns are added
+const SLOT_WIDTH: f64 = 110.0;
+const SLOT_HEIGHT: f64 = 26.0;
 
-// Number of dice in a turn
-pub const HAND_SIZE: usize = 5;
-
-/// Each possible option
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum ScoreType {
-    Ones(u8),
-    Twos(u8),
-    Threes(u8),
-    Fours(u8),
-    Fives(u8),
-    Sixes(u8),
-    ThreeKind,
-    FourKind,
-    TwoAndThree,
-    SmStraight,
-    LgStraight,
-    AllFive,
-    AllFiveBonus(u8),
-    StoneSoup(u8),
+// Width of the category names down the left of the score table, and the odds down its right
+const LABEL_WIDTH: f64 = 110.0;
+const CHANCE_WIDTH: f64 = 50.0;
+
+// Dice side by side before the hand wraps onto another row
+const DICE_PER_ROW: usize = 6;
+
+// Pauses between computer moves to choose from, in milliseconds
+const AI_DELAYS: [u32; 5] = [0, 250, 500, 1000, 2000];
+
+// Outline for whatever a hint recommends, apart from the red of held dice
+const HINT_COLOR: &str = "green";
+
+/// Which screen is showing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    Game,
+    Records,
+    Review,
 }
 
-/*
-impl ScoreType {
-    /// Return whether this score can be taken from the current hand
-    fn is_valid(&self, _hand: &Hand) -> bool {
-        use ScoreType::*;
-        match self {
-            Ones(_) | Twos(_) | Threes(_) | Fours(_) | Fives(_) | Sixes(_) => true,
-            ThreeKind => false,
-            FourKind => false,
-            TwoAndThree => false,
-            SmStraight => false,
-            LgStraight => false,
-            AllFive => false,
-            AllFiveBonus(_) => false,
-            StoneSoup(_) => true,
-        }
-    }
+/// Everything a click can ask for - a move in the game, or a different screen
+#[derive(Debug, Clone, Copy)]
+pub enum UiMessage {
+    Play(FiveDiceMessage),
+    /// A move made from one seat's scorecard, turned down unless it's that seat's turn
+    PlayAs(usize, FiveDiceMessage),
+    /// Milliseconds between computer moves
+    SetDelay(u32),
+    Show(Screen),
+    /// Ask the solver what the active player should do
+    Hint,
+    /// Move the review up or down by this many decisions
+    Scroll(i32),
 }
-*/
 
-/// A single score option
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct ScoreSlot {
-    taken: bool,
-    value: ScoreType,
+/// What the solver recommended, until the next move is made
+#[derive(Debug, Clone)]
+enum Hint {
+    /// Which dice to hold, and the expected final total for holding them
+    Hold(Vec<bool>, f64),
+    /// Which category to take, and the expected final total for taking it
+    Score(ScoreType, f64),
+    /// There are no tables for this game's rules
+    Unavailable,
 }
 
-impl ScoreSlot {
-    fn new(value: ScoreType) -> Self {
-        Self {
-            taken: false,
-            value,
-        }
-    }
+/// Whether a move belongs to whoever's turn it is, rather than the whole table
+fn is_turn_move(msg: FiveDiceMessage) -> bool {
+    matches!(
+        msg,
+        FiveDiceMessage::HoldDie(_) | FiveDiceMessage::RollDice | FiveDiceMessage::TakeScore(..)
+    )
 }
 
-impl Widget for ScoreSlot {
-    type MSG = FiveDiceMessage;
-    fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
-        let mut ret = MountedWidget::new(top_left);
-        let button = Button::new(&format!("Add {:?}", self.value));
-        ret.push_current_row(Box::new(button));
-        ret
-    }
-    fn handle_click(
-        &mut self,
-        _top_left: Point,
-        _click: Point,
-        _w: WindowPtr,
-    ) -> WindowResult<Option<Self::MSG>> {
-        Ok(None)
+/// Text shown for a slot when it can't be clicked
+fn slot_label(slot: &ScoreSlot) -> String {
+    if slot.is_open() {
+        "-".into()
+    } else {
+        slot.points().to_string()
     }
 }
 
-/// A single player's score object
+/// Text held to a fixed width, so the cells of a scorecard line up in columns
+/// Anything too long for the width pushes the rest of its row along
 #[derive(Debug, Clone)]
-struct Score {
-    slots: HashSet<ScoreSlot>,
+struct Cell {
+    text: String,
+    width: f64,
 }
 
-impl Score {
-    fn new() -> Self {
-        Self::default()
+impl Cell {
+    fn new(text: &str, width: f64) -> Self {
+        Self {
+            text: text.into(),
+            width,
+        }
     }
 }
 
-impl Default for Score {
-    fn default() -> Self {
-        use ScoreType::*;
-        let mut ret = Self {
-            slots: HashSet::new(),
-        };
-        ret.slots.insert(ScoreSlot::new(Ones(0)));
-        ret.slots.insert(ScoreSlot::new(Twos(0)));
-        ret.slots.insert(ScoreSlot::new(Threes(0)));
-        ret.slots.insert(ScoreSlot::new(Fours(0)));
-        ret.slots.insert(ScoreSlot::new(Fives(0)));
-        ret.slots.insert(ScoreSlot::new(Sixes(0)));
-        ret.slots.insert(ScoreSlot::new(ThreeKind));
-        ret.slots.insert(ScoreSlot::new(FourKind));
-        ret.slots.insert(ScoreSlot::new(TwoAndThree));
-        ret.slots.insert(ScoreSlot::new(SmStraight));
-        ret.slots.insert(ScoreSlot::new(LgStraight));
-        ret.slots.insert(ScoreSlot::new(AllFive));
-        ret.slots.insert(ScoreSlot::new(AllFiveBonus(0)));
-        ret.slots.insert(ScoreSlot::new(StoneSoup(0)));
-        ret
+impl Drawable for Cell {
+    fn draw_at(&self, top_left: Point, w: WindowPtr) -> WindowResult<Point> {
+        w.begin_path();
+        w.text(&self.text, &format!("{}", Font::default()), top_left)?;
+        w.draw_path();
+        Ok(Drawable::get_region(self, top_left, w)?.bottom_right())
+    }
+
+    fn get_region(&self, top_left: Point, w: WindowPtr) -> WindowResult<Region> {
+        let width = w.text_width(&self.text)?.max(self.width);
+        Ok((top_left, width, Font::default().height()).into())
     }
 }
 
-impl Widget for Score {
-    type MSG = FiveDiceMessage;
+impl Widget for Cell {
+    type MSG = UiMessage;
     fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
         let mut ret = MountedWidget::new(top_left);
-        // first in first row
-        for slot in &self.slots {
-            ret.push_current_row(Box::new(*slot));
-        }
+        ret.set_drawable(Box::new(self.clone()));
         ret
     }
     fn handle_click(
         &mut self,
-        _top_left: Point,
-        _click: Point,
-        _w: WindowPtr,
+        _: Point,
+        _: Point,
+        _: WindowPtr,
     ) -> WindowResult<Option<Self::MSG>> {
         Ok(None)
     }
 }
 
-/// Each possible Die result
-#[derive(Debug, Clone, Copy)]
-pub enum RollResult {
-    One = 1,
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
+/// A single open score option, as a button showing what the current hand would score
+/// Scratches are outlined in blue so they aren't taken by accident
+fn slot_button(
+    slot: &ScoreSlot,
+    seat: usize,
+    preview: u16,
+    hint: bool,
+    width: f64,
+) -> Button<UiMessage> {
+    let (category, column) = (slot.category(), slot.column());
+    let mut button = if preview > 0 {
+        Button::new(&format!("+{}", preview))
+    } else {
+        // Narrow cells have no room to spell it out
+        let mut b = Button::new(if width < SLOT_WIDTH { "0" } else { "scratch" });
+        b.add_border_color(Color::from_str("blue").unwrap());
+        b
+    };
+    if hint {
+        button.add_border_color(Color::from_str(HINT_COLOR).unwrap());
+    }
+    button.set_onclick(Callback::from(move || -> UiMessage {
+        UiMessage::PlayAs(seat, FiveDiceMessage::TakeScore(category, column))
+    }));
+    button.set_size(width, SLOT_HEIGHT);
+    button
 }
 
-/// A single Die, can be held or not
-#[derive(Debug, Clone, Copy)]
-pub struct Die {
-    id: u8,
-    value: RollResult,
-    held: bool,
+/// An open slot on a human's card while someone else plays - clicking it only says whose
+/// turn it is
+fn waiting_button(slot: &ScoreSlot, seat: usize, width: f64) -> Button<UiMessage> {
+    let (category, column) = (slot.category(), slot.column());
+    let mut button = Button::new(&slot_label(slot));
+    button.set_onclick(Callback::from(move || -> UiMessage {
+        UiMessage::PlayAs(seat, FiveDiceMessage::TakeScore(category, column))
+    }));
+    button.set_size(width, SLOT_HEIGHT);
+    button
 }
 
-impl Die {
-    fn new(id: u8, value: RollResult) -> Self {
-        Self {
-            id,
-            value,
-            held: false,
-        }
+/// A single player's scorecard, as one block of columns in the score table
+#[derive(Debug, Clone)]
+struct ScoreCard {
+    name: String,
+    score: Score,
+    // Whose turn it is - only then do a human's open slots preview the hand
+    active: bool,
+    controller: ControllerKind,
+    // Position at the table, for handing the seat over
+    seat: usize,
+    // The hand being previewed against this card
+    hand: Hand,
+    // The category a hint recommends, if any - hints only know the first column
+    hint: Option<ScoreType>,
+}
+
+/// Every player's scorecard in one table - the category names once down the left, then a
+/// block of columns per player, narrowed so the whole table fits the canvas
+#[derive(Debug, Clone)]
+struct ScoreTable {
+    cards: Vec<ScoreCard>,
+    rules: Ruleset,
+    // Before the first score, each header cycles who plays that card
+    setup: bool,
+    // Whether the game is still going, so open slots can be clicked at all
+    playing: bool,
+    // Chance of filling each category this turn, for the card whose turn it is
+    chances: Option<Vec<(ScoreType, f64)>>,
+}
+
+impl ScoreTable {
+    /// How wide each score cell can be with every player's columns side by side
+    fn cell_width(&self) -> f64 {
+        let cells = (self.cards.len() * self.rules.columns().len()).max(1) as f64;
+        let room = CANVAS_WIDTH - LABEL_WIDTH - CHANCE_WIDTH - 4.0 * PADDING;
+        (room / cells - PADDING).min(SLOT_WIDTH)
     }
 
-    /// Get a random die
-    fn get_random(id: u8) -> Self {
-        Self::new(id, Self::get_random_result())
+    /// How wide one player's block of columns is
+    fn card_width(&self) -> f64 {
+        let columns = self.rules.columns().len() as f64;
+        columns * (self.cell_width() + PADDING) - PADDING
     }
 
-    /// Get a random result
-    fn get_random_result() -> RollResult {
-        use RollResult::*;
-        match js_gen_range(1, 7) {
-            1 => One,
-            2 => Two,
-            3 => Three,
-            4 => Four,
-            5 => Five,
-            6 => Six,
-            _ => unreachable!(),
+    /// Push a category's row - its name, then a cell for every column of every card, as a
+    /// button only where it can be chosen right now
+    fn push_category(&self, ret: &mut MountedWidget<UiMessage>, category: ScoreType) {
+        let width = self.cell_width();
+        ret.push_new_row(Box::new(Cell::new(&format!("{:?}", category), LABEL_WIDTH)));
+        for card in &self.cards {
+            let human = card.controller == ControllerKind::Human;
+            for column in 0..card.score.columns() {
+                let slot = match card.score.slot_in(column, category) {
+                    Some(slot) => *slot,
+                    None => continue,
+                };
+                let can_take = card
+                    .score
+                    .can_take(column, category, &card.hand, &self.rules);
+                if card.active && human && can_take {
+                    let preview = card
+                        .score
+                        .points_for(column, category, &card.hand, &self.rules);
+                    let hint = column == 0 && card.hint == Some(category);
+                    let button = slot_button(&slot, card.seat, preview, hint, width);
+                    ret.push_current_row(Box::new(button));
+                } else if self.playing && !card.active && human && slot.is_open() {
+                    ret.push_current_row(Box::new(waiting_button(&slot, card.seat, width)));
+                } else {
+                    ret.push_current_row(Box::new(Cell::new(&slot_label(&slot), width)));
+                }
+            }
+        }
+        let open = self
+            .cards
+            .iter()
+            .filter(|card| card.active)
+            .any(|card| (0..card.score.columns()).any(|c| card.score.is_open_in(c, category)));
+        let chance = self
+            .chances
+            .iter()
+            .flatten()
+            .find(|(c, _)| *c == category)
+            .filter(|_| open);
+        if let Some((_, chance)) = chance {
+            ret.push_current_row(Box::new(Text::new(&percent(*chance))));
         }
     }
 
-    /// Roll this die - no action if currently held
-    fn roll(&mut self) {
-        if !self.held {
-            self.value = Self::get_random_result();
+    /// Push a totals row - its name, then a figure for every column of every card
+    fn push_totals(
+        &self,
+        ret: &mut MountedWidget<UiMessage>,
+        label: &str,
+        figure: impl Fn(&Score, usize) -> String,
+    ) {
+        let width = self.cell_width();
+        ret.push_new_row(Box::new(Cell::new(label, LABEL_WIDTH)));
+        for card in &self.cards {
+            for column in 0..card.score.columns() {
+                ret.push_current_row(Box::new(Cell::new(&figure(&card.score, column), width)));
+            }
         }
     }
+}
 
-    /// Toggles whether this die is held
-    fn toggle_held(&mut self) {
-        self.held = !self.held;
+impl Widget for ScoreTable {
+    type MSG = UiMessage;
+    fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
+        let mut ret = MountedWidget::new(top_left);
+        // Player names over their blocks, outlined in red on their turn
+        ret.push_current_row(Box::new(Cell::new("", LABEL_WIDTH)));
+        for card in &self.cards {
+            let mut header = match card.controller {
+                ControllerKind::Human => Button::new(&card.name),
+                kind => Button::new(&format!("{} ({:?})", card.name, kind)),
+            };
+            if card.active {
+                header.add_border_color(Color::from_str("red").unwrap());
+            }
+            if self.setup {
+                let (seat, next) = (card.seat, card.controller.next());
+                header.set_onclick(Callback::from(move || -> UiMessage {
+                    UiMessage::Play(FiveDiceMessage::SetController(seat, next))
+                }));
+            }
+            header.set_size(self.card_width(), SLOT_HEIGHT);
+            ret.push_current_row(Box::new(header));
+        }
+        // Column headers, showing what each column counts for
+        let weighted = self.rules.columns().len() > 1;
+        if weighted {
+            self.push_totals(&mut ret, "", |score, column| {
+                format!("×{}", score.weight(column))
+            });
+        }
+        // Upper section, then its totals
+        let categories: Vec<ScoreType> = self.rules.categories().collect();
+        for category in categories.iter().filter(|c| c.is_upper()) {
+            self.push_category(&mut ret, *category);
+        }
+        self.push_totals(&mut ret, "Upper", |score, column| {
+            score.column_upper_subtotal(column).to_string()
+        });
+        // Not every ruleset has an upper bonus to chase - short of it, show what's still needed
+        if !self.rules.upper_bonus().is_empty() {
+            self.push_totals(&mut ret, "Bonus", |score, column| {
+                match score.column_upper_bonus(column) {
+                    0 => format!("({})", score.bonus_needed(column)),
+                    bonus => bonus.to_string(),
+                }
+            });
+        }
+        // Lower section, then the lower and column totals
+        for category in categories.iter().filter(|c| !c.is_upper()) {
+            self.push_category(&mut ret, *category);
+        }
+        if self.rules.all_five_bonus() > 0 {
+            self.push_totals(&mut ret, "AllFiveBonus", |score, column| {
+                score
+                    .slot_in(column, ScoreType::AllFiveBonus)
+                    .map_or(0, ScoreSlot::points)
+                    .to_string()
+            });
+        }
+        self.push_totals(&mut ret, "Lower", |score, column| {
+            score.column_lower_subtotal(column).to_string()
+        });
+        self.push_totals(&mut ret, "Total", |score, column| {
+            score.column_total(column).to_string()
+        });
+        // Each column's total counted its weight times over, then each card's grand total
+        if weighted {
+            self.push_totals(&mut ret, "Weighted", |score, column| {
+                score
+                    .weight(column)
+                    .saturating_mul(score.column_total(column))
+                    .to_string()
+            });
+            ret.push_new_row(Box::new(Cell::new("Grand total", LABEL_WIDTH)));
+            for card in &self.cards {
+                let total = card.score.total().to_string();
+                ret.push_current_row(Box::new(Cell::new(&total, self.card_width())));
+            }
+        }
+        ret
+    }
+    fn handle_click(
+        &mut self,
+        top_left: Point,
+        click: Point,
+        w: WindowPtr,
+    ) -> WindowResult<Option<Self::MSG>> {
+        let mut mw: MountedWidget<Self::MSG> = self.mount_widget(top_left);
+        mw.click(click, w)
     }
 }
 
 // TODO make it easy to impl Widget for items that are Drawable already
 // I smell a macro DSL?  Just one variadic macro should do it at first
 
-impl Widget for Die {
-    type MSG = FiveDiceMessage;
+/// A single die, as a button that toggles whether it's held
+#[derive(Debug, Clone, Copy)]
+struct DieWidget {
+    die: Die,
+    // Whether a hint recommends holding this die
+    hint: bool,
+}
+
+impl Widget for DieWidget {
+    type MSG = UiMessage;
     fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
         let mut ret = MountedWidget::new(top_left);
         // Will get moved into closure - cannot call self inside, lifetime conflict (need 'static)
-        let id = self.id as usize;
-        let die_color = if self.held {
+        let id = self.die.id() as usize;
+        let die_color = if self.hint {
+            Color::from_str(HINT_COLOR).unwrap()
+        } else if self.die.is_held() {
             Color::from_str("red").unwrap()
         } else {
             Color::from_str("black").unwrap()
         };
-        let mut button = Button::new(&format!("{:?}", self.value));
+        let mut button = Button::new(&format!("{:?}", self.die.value()));
         button.add_border_color(die_color);
-        button.set_onclick(Callback::from(move || -> FiveDiceMessage {
-            FiveDiceMessage::HoldDie(id)
+        button.set_onclick(Callback::from(move || -> UiMessage {
+            UiMessage::Play(FiveDiceMessage::HoldDie(id))
         }));
         button.set_size(50.0, 50.0);
         ret.push_current_row(Box::new(button));
@@ -240,65 +435,42 @@ impl Widget for Die {
     ) -> WindowResult<Option<Self::MSG>> {
         // TODO this is identical to hand, no need to write every time
         let mut mw: MountedWidget<Self::MSG> = self.mount_widget(top_left);
-        Ok(mw.click(click, w)?)
+        mw.click(click, w)
     }
 }
 
-/// A set of 5 dice for a single play
-#[derive(Debug, Clone, Copy)]
-pub struct Hand {
-    pub dice: [Die; HAND_SIZE],
-    pub remaining_rolls: u8,
-}
-
-impl Hand {
-    fn new() -> Self {
-        Self::default()
-    }
-
-    /// all unheld dice if there are rolls left
-    pub fn roll(&mut self) {
-        if self.remaining_rolls > 0 {
-            for die in self.dice.iter_mut() {
-                die.roll();
-            }
-            self.remaining_rolls -= 1;
-        }
-    }
-}
-
-impl Default for Hand {
-    fn default() -> Self {
-        Self {
-            // HAND_SIZE is hard-coded to 5 - TODO do this in a loop?
-            dice: [
-                Die::get_random(0),
-                Die::get_random(1),
-                Die::get_random(2),
-                Die::get_random(3),
-                Die::get_random(4),
-            ],
-            remaining_rolls: 3,
-        }
-    }
+/// The active hand, with its roll button
+#[derive(Debug, Clone)]
+struct HandWidget {
+    hand: Hand,
+    // The dice a hint recommends holding, if any
+    hint: Option<Vec<bool>>,
 }
 
-impl Widget for Hand {
-    type MSG = FiveDiceMessage;
+impl Widget for HandWidget {
+    type MSG = UiMessage;
     fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
         let mut ret = MountedWidget::new(top_left);
-        for die in &self.dice {
-            ret.push_current_row(Box::new(*die));
+        for (idx, die) in self.hand.dice.iter().enumerate() {
+            let die = Box::new(DieWidget {
+                die: *die,
+                hint: self.hint.as_ref().is_some_and(|held| held[idx]),
+            });
+            if idx > 0 && idx % DICE_PER_ROW == 0 {
+                ret.push_new_row(die);
+            } else {
+                ret.push_current_row(die);
+            }
         }
         // TODO the reroll button only picks up clicks on the bottom half of the button
         let mut button = Button::new("Roll!");
         button.set_onclick(Callback::from(|| -> Self::MSG {
-            FiveDiceMessage::RollDice
+            UiMessage::Play(FiveDiceMessage::RollDice)
         }));
         ret.push_new_row(Box::new(button));
         ret.push_current_row(Box::new(Text::new(&format!(
             "Remaining rolls: {}",
-            self.remaining_rolls
+            self.hand.remaining_rolls
         ))));
         ret
     }
@@ -313,97 +485,379 @@ impl Widget for Hand {
     }
 }
 
-/// The Player object
+/// The whole game as a widget
 #[derive(Debug)]
-struct Player {
-    score: Score,
-    current_hand: Hand,
+pub struct GameWidget {
+    // The game, logged so it can be replayed
+    recorder: Recorder,
+    // Why the last click was turned down, if it was
+    rejection: Option<String>,
+    // Where the game is saved after every accepted click
+    storage: LocalStorage,
+    screen: Screen,
+    records: Records,
+    // Whether the finished game has gone into the records yet
+    recorded: bool,
+    // Milliseconds to wait before each computer move, and when the last move was made
+    ai_delay: u32,
+    last_move: f64,
+    // The solver's advice for the current position, shown until the next move
+    hint: Option<Hint>,
+    odds: Odds,
+    // The active hand's chance of filling each category, worked out again only when it changes
+    chances: Vec<(ScoreType, f64)>,
+    // The finished game's decisions, worked out when the review is opened
+    review: Review,
+    review_offset: usize,
 }
 
-impl Player {
-    fn new() -> Self {
-        Self {
-            current_hand: Hand::new(),
-            score: Score::new(),
-        }
-    }
-
-    fn get_hand(&self) -> Box<Hand> {
-        Box::new(self.current_hand)
-    }
-}
-
-// All the various ways the game can be interacted with
-#[derive(Debug, Clone, Copy)]
-pub enum FiveDiceMessage {
-    HoldDie(usize),
-    RollDice,
-    StartOver,
-}
-
-/// The Game object
-#[derive(Debug)]
-pub struct Game {
-    // For now, just a solo game
-    player: Player,
-    score: Score,
-}
-
-impl Game {
+impl GameWidget {
+    /// Resume the saved game if there is a usable one, otherwise start fresh
     pub fn new() -> Self {
+        let storage = LocalStorage;
+        let recorder = match load(&storage) {
+            Ok(Some(recorder)) => recorder,
+            Ok(None) => Recorder::new(js_seed()),
+            Err(e) => {
+                console::warn_1(&format!("Starting a new game: {}", e).into());
+                Recorder::new(js_seed())
+            }
+        };
+        let records = Records::load(&storage).unwrap_or_else(|e| {
+            console::warn_1(&format!("Starting new records: {}", e).into());
+            Records::default()
+        });
+        // A game that was already over when saved has been recorded
+        let recorded = recorder.game().phase() == GamePhase::Finished;
+        let odds = Odds::new(recorder.game().rules());
+        let chances = odds.chances(recorder.game().active_player().hand());
         Self {
-            player: Player::new(),
-            score: Score::new(),
+            recorder,
+            rejection: None,
+            storage,
+            screen: Screen::Game,
+            records,
+            recorded,
+            ai_delay: AI_DELAYS[2],
+            last_move: 0.0,
+            hint: None,
+            odds,
+            chances,
+            review: Review::default(),
+            review_offset: 0,
         }
     }
 
-    /// Get a pointer to the current score
-    fn get_score(&self) -> &Score {
-        &self.score
+    /// Whether the active player is played by the computer
+    fn computer_to_play(&self) -> bool {
+        let game = self.recorder.game();
+        game.phase() == GamePhase::Playing
+            && game.active_player().controller() != ControllerKind::Human
     }
 
-    // Toggle one die on the player
-    fn hold_die(&mut self, die_idx: usize) {
-        if die_idx < HAND_SIZE {
-            self.player.current_hand.dice[die_idx].toggle_held();
-        }
+    /// Run a move through the game for whoever's turn it is
+    fn play(&mut self, msg: FiveDiceMessage) {
+        self.play_as(self.recorder.game().active(), msg);
     }
 
-    /// Handle all incoming messages
-    /// TODO send an outgoing result?  Maybe use the memory tape for streaming events back
-    fn reducer(&mut self, msg: FiveDiceMessage) {
-        use FiveDiceMessage::*;
-        match msg {
-            HoldDie(idx) => self.hold_die(idx),
-            RollDice => self.roll_dice(),
-            StartOver => self.reset(),
+    /// Run a move through the game for a seat, then save and record whatever came of it
+    fn play_as(&mut self, seat: usize, msg: FiveDiceMessage) {
+        self.last_move = Date::now();
+        // Hold on to the reason if it's turned down
+        self.rejection = self
+            .recorder
+            .reduce_as(seat, msg)
+            .err()
+            .map(|e| FiveDiceError::from(e).to_string());
+        if self.rejection.is_some() {
+            return;
+        }
+        // Asking for a hint is a move too, but it doesn't change the position
+        if msg != FiveDiceMessage::Hint {
+            self.hint = None;
+        }
+        // New rules mean new categories to work the odds out for
+        if let FiveDiceMessage::SetVariant(_) = msg {
+            self.odds = Odds::new(self.recorder.game().rules());
+        }
+        // Every other move can change the hand, what's held, or whose it is
+        if msg != FiveDiceMessage::Hint {
+            self.chances = self
+                .odds
+                .chances(self.recorder.game().active_player().hand());
+        }
+        // Keep the save in step so a refresh doesn't lose anything
+        if let Err(e) = save(&mut self.storage, &self.recorder) {
+            console::warn_1(&FiveDiceError::from(e).to_string().into());
+        }
+        // Each game goes in the records once, the first time it finishes
+        let game = self.recorder.game();
+        if game.phase() == GamePhase::Finished {
+            if !self.recorded {
+                self.records.record_game(game, &today());
+                if let Err(e) = self.records.store(&mut self.storage) {
+                    console::warn_1(&FiveDiceError::from(e).to_string().into());
+                }
+                self.recorded = true;
+            }
+        } else if game.turn() == 0 {
+            self.recorded = false;
         }
     }
 
-    /// Start a fresh new game
-    fn reset(&mut self) {
-        self.player = Player::new();
+    /// Ask the solver about the active player's position, counting it against them
+    fn ask_hint(&mut self) {
+        let game = self.recorder.game();
+        let player = game.active_player();
+        let (score, hand) = (player.score(), player.hand());
+        // Tables solved for other rules would only mislead
+        let advice = strategy().filter(|s| s.fits(game.rules())).and_then(|s| {
+            if hand.remaining_rolls > 0 {
+                s.advise_hold(score, hand)
+                    .map(|a| Hint::Hold(a.held, a.expected))
+            } else {
+                s.advise_score(score, hand)
+                    .map(|a| Hint::Score(a.category, a.expected))
+            }
+        });
+        match advice {
+            Some(hint) => {
+                self.play(FiveDiceMessage::Hint);
+                if self.rejection.is_none() {
+                    self.hint = Some(hint);
+                }
+            }
+            None => self.hint = Some(Hint::Unavailable),
+        }
     }
 
-    /// Roll all unheld dice
-    fn roll_dice(&mut self) {
-        self.player.current_hand.roll();
+    /// Work out the review and show it, if there are tables to judge by
+    fn open_review(&mut self) {
+        let strategy = match strategy() {
+            Some(strategy) => strategy,
+            None => {
+                self.rejection =
+                    Some("Reviews need the strategy tables - run make strategy".into());
+                return;
+            }
+        };
+        match Review::analyse(self.recorder.log(), &strategy) {
+            Ok(review) => {
+                self.review = review;
+                self.review_offset = 0;
+                self.screen = Screen::Review;
+                self.rejection = None;
+            }
+            Err(e) => self.rejection = Some(FiveDiceError::from(e).to_string()),
+        }
     }
-}
 
-impl Widget for Game {
-    type MSG = FiveDiceMessage;
-    fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
+    /// The board itself - controls, the hand and every scorecard
+    fn mount_game(&self, top_left: Point) -> MountedWidget<UiMessage> {
+        let game = self.recorder.game();
         let mut ret = MountedWidget::new(top_left);
         let mut start_over_button = Button::new("Start Over");
-        start_over_button.set_onclick(Callback::from(|| -> Self::MSG {
-            FiveDiceMessage::StartOver
+        start_over_button.set_onclick(Callback::from(|| -> UiMessage {
+            UiMessage::Play(FiveDiceMessage::StartOver)
         }));
         ret.push_current_row(Box::new(start_over_button));
-        ret.push_new_row(self.player.get_hand());
-        ret.push_new_row(Box::new(self.get_score().clone()));
+        let mut records_button = Button::new("High Scores");
+        records_button.set_onclick(Callback::from(|| -> UiMessage {
+            UiMessage::Show(Screen::Records)
+        }));
+        ret.push_current_row(Box::new(records_button));
+        let next_delay = AI_DELAYS
+            .iter()
+            .cycle()
+            .skip_while(|d| **d != self.ai_delay)
+            .nth(1)
+            .copied()
+            .unwrap_or(AI_DELAYS[0]);
+        let mut delay_button = Button::new(&format!("AI delay: {}ms", self.ai_delay));
+        delay_button.set_onclick(Callback::from(move || -> UiMessage {
+            UiMessage::SetDelay(next_delay)
+        }));
+        ret.push_current_row(Box::new(delay_button));
+        if game.can_undo() {
+            let mut undo_button = Button::new("Undo");
+            undo_button.set_onclick(Callback::from(|| -> UiMessage {
+                UiMessage::Play(FiveDiceMessage::Undo)
+            }));
+            ret.push_current_row(Box::new(undo_button));
+        }
+        if game.can_redo() {
+            let mut redo_button = Button::new("Redo");
+            redo_button.set_onclick(Callback::from(|| -> UiMessage {
+                UiMessage::Play(FiveDiceMessage::Redo)
+            }));
+            ret.push_current_row(Box::new(redo_button));
+        }
+        let hand = game.active_player().hand();
+        if game.phase() == GamePhase::Playing && !self.computer_to_play() && hand.has_rolled() {
+            let mut hint_button = Button::new("Hint");
+            hint_button.set_onclick(Callback::from(|| -> UiMessage { UiMessage::Hint }));
+            ret.push_current_row(Box::new(hint_button));
+        }
+        // Rules and players can only change before the first score
+        if game.turn() == 0 {
+            let next_variant = Variant::of(game.rules()).unwrap_or_default().next();
+            let mut rules_button = Button::new(&format!("Rules: {}", game.rules().name()));
+            rules_button.set_onclick(Callback::from(move || -> UiMessage {
+                UiMessage::Play(FiveDiceMessage::SetVariant(next_variant))
+            }));
+            ret.push_current_row(Box::new(rules_button));
+            // Jokers only matter where extra five of a kinds earn a bonus
+            if game.rules().all_five_bonus() > 0 {
+                let next_rule = game.joker_rule().next();
+                let mut joker_button = Button::new(&format!("Jokers: {:?}", game.joker_rule()));
+                joker_button.set_onclick(Callback::from(move || -> UiMessage {
+                    UiMessage::Play(FiveDiceMessage::SetJokerRule(next_rule))
+                }));
+                ret.push_current_row(Box::new(joker_button));
+            }
+            if game.players().len() < MAX_PLAYERS {
+                let mut add_button = Button::new("Add Player");
+                add_button.set_onclick(Callback::from(|| -> UiMessage {
+                    UiMessage::Play(FiveDiceMessage::AddPlayer)
+                }));
+                ret.push_current_row(Box::new(add_button));
+            }
+            if game.players().len() > 1 {
+                let mut remove_button = Button::new("Remove Player");
+                remove_button.set_onclick(Callback::from(|| -> UiMessage {
+                    UiMessage::Play(FiveDiceMessage::RemovePlayer)
+                }));
+                ret.push_current_row(Box::new(remove_button));
+            }
+        }
+        match game.phase() {
+            GamePhase::Playing => {
+                // What the solver expects this card to finish on, once its tables are in
+                let par = strategy()
+                    .filter(|s| s.fits(game.rules()))
+                    .map(|s| {
+                        format!(
+                            " (par {:.0})",
+                            s.expected_total(game.active_player().score())
+                        )
+                    })
+                    .unwrap_or_default();
+                ret.push_current_row(Box::new(Text::new(&format!(
+                    "Round {} of {} - {} to roll{}",
+                    game.round(),
+                    game.rounds(),
+                    game.active_player().name(),
+                    par
+                ))));
+                let held = match &self.hint {
+                    Some(Hint::Hold(held, _)) => Some(held.clone()),
+                    _ => None,
+                };
+                ret.push_new_row(Box::new(HandWidget {
+                    hand: game.active_player().hand().clone(),
+                    hint: held,
+                }));
+            }
+            GamePhase::Finished => {
+                ret.push_current_row(Box::new(Text::new("Game over!")));
+                let mut review_button = Button::new("Review Game");
+                review_button.set_onclick(Callback::from(|| -> UiMessage {
+                    UiMessage::Show(Screen::Review)
+                }));
+                ret.push_current_row(Box::new(review_button));
+                if let Some(winner) = game.winner() {
+                    ret.push_new_row(Box::new(Text::new(&format!(
+                        "{} was served a five of a kind and wins outright",
+                        game.players()[winner].name()
+                    ))));
+                }
+                for (rank, player) in game.standings() {
+                    ret.push_new_row(Box::new(Text::new(&format!(
+                        "{}. {}: {}",
+                        rank,
+                        player.name(),
+                        player.score().total()
+                    ))));
+                }
+            }
+        }
+        let hint_text = match self.hint {
+            Some(Hint::Hold(_, expected)) => Some(format!(
+                "Hint: hold the green dice - expect {:.1}",
+                expected
+            )),
+            Some(Hint::Score(category, expected)) => Some(format!(
+                "Hint: score {:?} - expect {:.1}",
+                category, expected
+            )),
+            Some(Hint::Unavailable) => Some(
+                "Hints need the standard rules and their tables - run make strategy".to_string(),
+            ),
+            None => None,
+        };
+        if let Some(text) = hint_text {
+            ret.push_new_row(Box::new(Text::new(&text)));
+        }
+        if let Some(reason) = &self.rejection {
+            ret.push_new_row(Box::new(Text::new(&format!("Not allowed: {}", reason))));
+        }
+        // Every scorecard side by side in one table
+        let playing = game.phase() == GamePhase::Playing;
+        let cards = game
+            .players()
+            .iter()
+            .enumerate()
+            .map(|(idx, player)| {
+                let active = playing && idx == game.active();
+                ScoreCard {
+                    name: player.name().into(),
+                    score: player.score().clone(),
+                    active,
+                    controller: player.controller(),
+                    seat: idx,
+                    hand: player.hand().clone(),
+                    hint: match self.hint {
+                        Some(Hint::Score(category, _)) if active => Some(category),
+                        _ => None,
+                    },
+                }
+            })
+            .collect();
+        ret.push_new_row(Box::new(ScoreTable {
+            cards,
+            rules: game.rules().clone(),
+            setup: game.turn() == 0,
+            playing,
+            chances: if playing {
+                Some(self.chances.clone())
+            } else {
+                None
+            },
+        }));
         ret
     }
+}
+
+impl Widget for GameWidget {
+    type MSG = UiMessage;
+    fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
+        match self.screen {
+            Screen::Game => self.mount_game(top_left),
+            Screen::Records => RecordsScreen(self.records.clone()).mount_widget(top_left),
+            Screen::Review => ReviewScreen {
+                review: self.review.clone(),
+                names: self
+                    .recorder
+                    .game()
+                    .players()
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect(),
+                offset: self.review_offset,
+            }
+            .mount_widget(top_left),
+        }
+    }
     fn handle_click(
         &mut self,
         top_left: Point,
@@ -413,11 +867,45 @@ impl Widget for Game {
         // Mount the widget and collect any message for this click point
         let mut mw: MountedWidget<Self::MSG> = self.mount_widget(top_left);
         let msg = mw.click(click, w)?;
-        if let Some(m) = msg {
-            // Handle the click
-            self.reducer(m);
+        match msg {
+            // Clicks can't move for a computer player
+            Some(UiMessage::Play(m)) if self.computer_to_play() && is_turn_move(m) => {}
+            Some(UiMessage::Play(m)) => self.play(m),
+            // A seat's own card can be clicked any time, to be told whose turn it is
+            Some(UiMessage::PlayAs(seat, m)) => self.play_as(seat, m),
+            Some(UiMessage::SetDelay(delay)) => self.ai_delay = delay,
+            Some(UiMessage::Hint) if !self.computer_to_play() => self.ask_hint(),
+            Some(UiMessage::Hint) => {}
+            Some(UiMessage::Show(Screen::Review)) => self.open_review(),
+            Some(UiMessage::Scroll(rows)) => {
+                let last = self.review.decisions().len().saturating_sub(1);
+                let offset = self.review_offset as i64 + i64::from(rows);
+                self.review_offset = offset.clamp(0, last as i64) as usize;
+            }
+            Some(UiMessage::Show(screen)) => {
+                self.screen = screen;
+                self.rejection = None;
+            }
+            None => {}
         }
         // Nothing to pass up to the caller
         Ok(None)
     }
+    fn tick(&mut self) -> WindowResult<()> {
+        // Computers only move on the game screen, and never faster than the delay
+        if self.screen != Screen::Game
+            || !self.computer_to_play()
+            || Date::now() - self.last_move < f64::from(self.ai_delay)
+        {
+            return Ok(());
+        }
+        let game = self.recorder.game();
+        // Tables solved for other rules would only mislead
+        let strategy = strategy().filter(|s| s.fits(game.rules()));
+        if let Some(mut controller) = game.active_player().controller().build(js_seed(), strategy) {
+            let msg = controller.next_move(game);
+            self.play(msg);
+        }
+        Ok(())
+    }
 }
@@ -2,29 +2,60 @@
 
 use wasm_bindgen::prelude::*;
 
+// Dice sources
+pub mod dice;
 // Error type
 mod error;
-// Game logic
+// Game rendering
 mod game;
+// High score and stats screen
+mod records;
+// Post-game decision review screen
+mod review;
+// Browser storage for saves
+mod storage;
+// Solver tables fetched by the page
+mod strategy;
 
-use crate::game::{FiveDiceMessage, Game};
-use widget_grid::window::{WebSysCanvas, WindowEngine};
+use crate::{
+    error::FiveDiceError,
+    game::{GameWidget, UiMessage, CANVAS_HEIGHT, CANVAS_WIDTH, PADDING},
+    strategy::set_strategy,
+};
+use fivedice_core::solver::Strategy;
+use widget_grid::{
+    types::Values,
+    window::{WebSysCanvas, WindowEngine},
+};
 
 /// Entry point for the module
 #[allow(dead_code)]
 #[wasm_bindgen(start)]
-pub fn start() {
+pub fn start() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
-    // Instantiate canvas
+    // Instantiate canvas - the score table narrows its cells to fit every player's columns
+    let values = Values {
+        canvas_region: (0.0, 0.0, CANVAS_WIDTH, CANVAS_HEIGHT).into(),
+        padding: PADDING,
+    };
     let renderable_context =
-        Box::new(WebSysCanvas::new("Five Dice").expect("Should instantiate canvas window engine"));
+        Box::new(WebSysCanvas::with_values("Five Dice", values).map_err(FiveDiceError::from)?);
 
     // Instantiate game
-    let game = Box::new(Game::new());
+    let game = Box::new(GameWidget::new());
 
     // Instantiate engine
-    let engine: WindowEngine<FiveDiceMessage> = WindowEngine::new(renderable_context, game);
+    let engine: WindowEngine<UiMessage> = WindowEngine::new(renderable_context, game);
 
     // Run game
     engine.start();
+    Ok(())
+}
+
+/// Hand over the solver tables once the page has fetched them
+#[wasm_bindgen]
+pub fn load_strategy(bytes: &[u8]) -> Result<(), JsValue> {
+    let strategy = Strategy::from_bytes(bytes).map_err(FiveDiceError::from)?;
+    set_strategy(strategy);
+    Ok(())
 }
@@ -0,0 +1,102 @@
+// records.rs renders the high score table and every player's lifetime stats
+
+use crate::game::{Screen, UiMessage};
+use fivedice_core::{
+    records::{Records, RulesetRecords},
+    scoring::ScoreType,
+};
+use js_sys::Date;
+use widget_grid::{
+    traits::{MountedWidget, Widget},
+    types::{Callback, Point},
+    widgets::{Button, Text},
+    window::WindowPtr,
+};
+
+type WindowResult<T> = widget_grid::error::Result<T>;
+
+/// Today's date as YYYY-MM-DD, from the browser's clock
+pub fn today() -> String {
+    let iso = String::from(Date::new_0().to_iso_string());
+    iso.chars().take(10).collect()
+}
+
+/// A fraction as a whole percentage
+pub(crate) fn percent(rate: f64) -> String {
+    format!("{:.0}%", rate * 100.0)
+}
+
+/// The high score and stats screen
+#[derive(Debug, Clone)]
+pub struct RecordsScreen(pub Records);
+
+impl Widget for RecordsScreen {
+    type MSG = UiMessage;
+    fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
+        let mut ret = MountedWidget::new(top_left);
+        let mut back_button = Button::new("Back to Game");
+        back_button.set_onclick(Callback::from(|| -> Self::MSG {
+            UiMessage::Show(Screen::Game)
+        }));
+        ret.push_current_row(Box::new(back_button));
+        if self.0.rulesets().next().is_none() {
+            ret.push_new_row(Box::new(Text::new("No finished games yet")));
+        }
+        // Totals only compare under the same rules, so each ruleset gets its own table
+        for (label, records) in self.0.rulesets() {
+            // Best games first
+            ret.push_new_row(Box::new(Text::new(&format!("High Scores - {}", label))));
+            for (idx, entry) in records.high_scores().iter().enumerate() {
+                ret.push_new_row(Box::new(Text::new(&format!(
+                    "{}. {}: {} - {}",
+                    idx + 1,
+                    entry.name,
+                    entry.total,
+                    entry.date
+                ))));
+            }
+            // Then a block per player
+            self.push_players(&mut ret, records);
+        }
+        ret
+    }
+    fn handle_click(
+        &mut self,
+        top_left: Point,
+        click: Point,
+        w: WindowPtr,
+    ) -> WindowResult<Option<Self::MSG>> {
+        let mut mw: MountedWidget<Self::MSG> = self.mount_widget(top_left);
+        mw.click(click, w)
+    }
+}
+
+impl RecordsScreen {
+    /// A line of stats for each player, with the categories they've scratched under it
+    fn push_players(&self, ret: &mut MountedWidget<UiMessage>, records: &RulesetRecords) {
+        for (name, stats) in records.players() {
+            ret.push_new_row(Box::new(Text::new(&format!(
+                "{}: {} games, average {:.1}, AllFive {}, upper bonus {}, hints {:.1}/game",
+                name,
+                stats.games(),
+                stats.average(),
+                percent(stats.all_five_rate()),
+                percent(stats.upper_bonus_rate()),
+                stats.hints_per_game()
+            ))));
+            // Only the categories that have actually been scratched
+            let scratched: Vec<String> = ScoreType::ALL
+                .iter()
+                .chain(ScoreType::EXTRA.iter())
+                .filter(|c| stats.scratch_rate(**c) > 0.0)
+                .map(|c| format!("{:?} {}", c, percent(stats.scratch_rate(*c))))
+                .collect();
+            if !scratched.is_empty() {
+                ret.push_new_row(Box::new(Text::new(&format!(
+                    "  Scratched: {}",
+                    scratched.join(", ")
+                ))));
+            }
+        }
+    }
+}
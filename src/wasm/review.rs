@@ -0,0 +1,137 @@
+// review.rs renders the post-game analysis, a page of decisions at a time
+
+use crate::game::{Screen, UiMessage};
+use fivedice_core::review::{Choice, Decision, Review};
+use std::str::FromStr;
+use widget_grid::{
+    traits::{MountedWidget, Widget},
+    types::{Callback, Color, Point},
+    widgets::{Button, Text},
+    window::WindowPtr,
+};
+
+type WindowResult<T> = widget_grid::error::Result<T>;
+
+/// Decisions shown at once - the rest are a scroll away
+const REVIEW_ROWS: usize = 30;
+
+/// A choice in words, showing the faces held rather than their positions
+fn describe(choice: &Choice, dice: &[u8]) -> String {
+    match choice {
+        Choice::Hold(held) => {
+            let faces: Vec<String> = dice
+                .iter()
+                .zip(held.iter())
+                .filter(|(_, held)| **held)
+                .map(|(face, _)| face.to_string())
+                .collect();
+            if faces.is_empty() {
+                "reroll all".into()
+            } else {
+                format!("hold {}", faces.join(" "))
+            }
+        }
+        Choice::Score(category) => format!("score {:?}", category),
+    }
+}
+
+/// One line per decision, with the cost only when something was given up
+fn decision_line(decision: &Decision, name: &str) -> String {
+    let dice: Vec<String> = decision.dice.iter().map(|face| face.to_string()).collect();
+    let mut ret = format!(
+        "Round {} {} [{}] {} left: {}",
+        decision.round,
+        name,
+        dice.join(" "),
+        decision.remaining_rolls,
+        describe(&decision.chosen, &decision.dice)
+    );
+    if decision.is_mistake() {
+        ret.push_str(&format!(
+            " - best {} - cost {:.2}",
+            describe(&decision.best, &decision.dice),
+            decision.cost()
+        ));
+    }
+    ret
+}
+
+/// The decision review screen, scrolled down to `offset`
+#[derive(Debug, Clone)]
+pub struct ReviewScreen {
+    pub review: Review,
+    /// Player names, by position at the table
+    pub names: Vec<String>,
+    pub offset: usize,
+}
+
+impl Widget for ReviewScreen {
+    type MSG = UiMessage;
+    fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG> {
+        let mut ret = MountedWidget::new(top_left);
+        let mut back_button = Button::new("Back to Game");
+        back_button.set_onclick(Callback::from(|| -> Self::MSG {
+            UiMessage::Show(Screen::Game)
+        }));
+        ret.push_current_row(Box::new(back_button));
+        let decisions = self.review.decisions();
+        if self.offset > 0 {
+            let mut up_button = Button::new("Scroll Up");
+            up_button.set_onclick(Callback::from(|| -> Self::MSG {
+                UiMessage::Scroll(-(REVIEW_ROWS as i32))
+            }));
+            ret.push_current_row(Box::new(up_button));
+        }
+        if self.offset + REVIEW_ROWS < decisions.len() {
+            let mut down_button = Button::new("Scroll Down");
+            down_button.set_onclick(Callback::from(|| -> Self::MSG {
+                UiMessage::Scroll(REVIEW_ROWS as i32)
+            }));
+            ret.push_current_row(Box::new(down_button));
+        }
+        // Totals first, so they're there whatever the scroll
+        ret.push_new_row(Box::new(Text::new(&format!(
+            "Equity lost: {:.2} over {} decisions",
+            self.review.equity_lost(),
+            decisions.len()
+        ))));
+        for (idx, name) in self.names.iter().enumerate() {
+            let mistakes = decisions
+                .iter()
+                .filter(|d| d.player == idx && d.is_mistake())
+                .count();
+            ret.push_new_row(Box::new(Text::new(&format!(
+                "{}: {:.2} lost in {} mistakes",
+                name,
+                self.review.equity_lost_by(idx),
+                mistakes
+            ))));
+        }
+        for decision in decisions.iter().skip(self.offset).take(REVIEW_ROWS) {
+            let name = self
+                .names
+                .get(decision.player)
+                .map(String::as_str)
+                .unwrap_or("?");
+            let line = decision_line(decision, name);
+            // Mistakes stand out in red, like a held die
+            if decision.is_mistake() {
+                let mut row = Button::new(&line);
+                row.add_border_color(Color::from_str("red").unwrap());
+                ret.push_new_row(Box::new(row));
+            } else {
+                ret.push_new_row(Box::new(Text::new(&line)));
+            }
+        }
+        ret
+    }
+    fn handle_click(
+        &mut self,
+        top_left: Point,
+        click: Point,
+        w: WindowPtr,
+    ) -> WindowResult<Option<Self::MSG>> {
+        let mut mw: MountedWidget<Self::MSG> = self.mount_widget(top_left);
+        mw.click(click, w)
+    }
+}
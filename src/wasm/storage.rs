@@ -0,0 +1,29 @@
+// storage.rs keeps saves in the browser's localStorage
+
+use fivedice_core::{
+    error::{GameError, Result},
+    save::Storage,
+};
+
+/// The browser's localStorage - empty if the page isn't allowed to use it
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        Self::storage()?.get_item(key).ok()?
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        Self::storage()
+            .ok_or(GameError::SaveFailed)?
+            .set_item(key, value)
+            .map_err(|_| GameError::SaveFailed)
+    }
+}
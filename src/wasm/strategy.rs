@@ -0,0 +1,19 @@
+// strategy.rs holds the solver tables once the page has fetched them
+
+use fivedice_core::solver::Strategy;
+use std::{cell::RefCell, rc::Rc};
+
+thread_local! {
+    // Empty until the page hands over strategy.bin
+    static STRATEGY: RefCell<Option<Rc<Strategy>>> = const { RefCell::new(None) };
+}
+
+/// Keep the loaded tables for the game to use
+pub fn set_strategy(strategy: Strategy) {
+    STRATEGY.with(|s| *s.borrow_mut() = Some(Rc::new(strategy)));
+}
+
+/// The loaded tables, if the page has handed any over yet
+pub fn strategy() -> Option<Rc<Strategy>> {
+    STRATEGY.with(|s| s.borrow().clone())
+}
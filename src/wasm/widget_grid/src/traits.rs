@@ -37,6 +37,10 @@ pub trait Widget {
     //     ret
     // }
     fn mount_widget(&self, top_left: Point) -> MountedWidget<Self::MSG>;
+    /// Called once a frame before drawing, for widgets that change without being clicked
+    fn tick(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// A container struct for a widget
@@ -41,9 +41,12 @@ pub struct WebSysCanvas {
 
 impl WebSysCanvas {
     pub fn new(title: &str) -> Result<Self> {
+        Self::with_values(title, Values::default())
+    }
+
+    /// Mount a canvas sized and padded by the given values
+    pub fn with_values(title: &str, values: Values) -> Result<Self> {
         console_error_panic_hook::set_once();
-        // set up Values
-        let values = Values::default();
         // Set up page
         let document = document();
         let body = body();
@@ -75,7 +78,7 @@ impl WebSysCanvas {
             .add_event_listener_with_callback("click", callback.as_ref().unchecked_ref())
             .expect("Should register event listener");
         callback.forget();
-        Ok(Self::default())
+        Ok(Self { ctx: ctx(), values })
     }
 }
 
@@ -162,6 +165,8 @@ impl<T> WindowEngine<T> {
             self.element
                 .handle_click(Point::default(), click, Rc::clone(&self.window))?;
         }
+        // then let the element move on by itself
+        self.element.tick()?;
         // clear canvas
         self.window.blank();
         // Draw element
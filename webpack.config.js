@@ -8,5 +8,9 @@ module.exports = {
     filename: "bootstrap.js"
   },
   mode: "development",
-  plugins: [new CopyWebpackPlugin(["./src/client/index.html"])]
+  plugins: [new CopyWebpackPlugin([
+    "./src/client/index.html",
+    // Solver tables, if they have been built
+    { from: "./src/client/*.bin", flatten: true }
+  ])]
 };